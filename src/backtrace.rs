@@ -0,0 +1,212 @@
+use std::fmt;
+use std::rc::Rc;
+use crate::compile::Code;
+
+/// Where a piece of dc:im code comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+	/// Code passed to [`Interpreter::exec`](crate::Interpreter::exec) or read by `?`.
+	Input,
+	/// File (or other named code) passed to [`Interpreter::exec_named`](crate::Interpreter::exec_named) or executed with `&` and `M`.
+	Named(String),
+	/// String executed as a macro.
+	Macro,
+}
+
+/// Position of a command in its source, lines and columns start at 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+	pub source: Source,
+	pub line: usize,
+	pub column: usize,
+}
+
+impl fmt::Display for Location {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match &self.source {
+			Source::Input => write!(f, "<input>")?,
+			Source::Named(name) => write!(f, "{}", name)?,
+			Source::Macro => write!(f, "<macro>")?,
+		}
+		write!(f, ":{}:{}", self.line, self.column)
+	}
+}
+
+/// Where an error happened and how execution got there.
+#[derive(Clone, Debug)]
+pub struct Backtrace {
+	/// Failing command.
+	pub location: Location,
+	/// Commands that invoked the macros leading to the failing command, innermost first.
+	/// Tail calls replace the calling macro, so a chain of them only shows its latest and first invocation.
+	pub calls: Vec<(char, Location)>,
+}
+
+//position in the code of a frame
+#[derive(Clone, Debug)]
+struct Site {
+	code: Rc<Code>,
+	source: Rc<Source>,
+	pos: usize,	//character index
+}
+
+impl Site {
+	fn locate(&self) -> Location {
+		let done = &self.code.text[..self.code.byte_pos(self.pos)];
+		let line_start = done.rfind('\n').map_or(0, |i| i+1);
+		Location {
+			source: (*self.source).clone(),
+			line: done.matches('\n').count() + 1,
+			column: done[line_start..].chars().count() + 1,
+		}
+	}
+}
+
+//element of the command stack: compiled code, how far it has been executed and where it comes from
+#[derive(Clone, Debug)]
+pub(crate) struct Frame {
+	code: Rc<Code>,
+	pc: usize,	//index of the next command
+	reps: usize,	//repetitions left after the current one
+	levels: usize,	//macro invocations for 'Q', more than 1 if finished callers were replaced by tail calls
+	source: Rc<Source>,
+	call: Option<(char, Site)>,	//invoking command and its position
+	tail: Option<(char, Site)>,	//invocation of the first frame replaced by tail calls leading to this one
+}
+
+impl Frame {
+	pub(crate) fn new(code: Rc<Code>, source: Source) -> Self {
+		Frame {
+			code,
+			pc: 0,
+			reps: 0,
+			levels: 1,
+			source: Rc::new(source),
+			call: None,
+			tail: None,
+		}
+	}
+
+	//code invoked by cmd, which started at character index pos of caller
+	pub(crate) fn called(code: Rc<Code>, source: Source, cmd: char, caller: &Frame, pos: usize) -> Self {
+		Frame {
+			call: Some((cmd, Site { code: Rc::clone(&caller.code), source: Rc::clone(&caller.source), pos })),
+			..Frame::new(code, source)
+		}
+	}
+
+	//execute n>0 times in total
+	pub(crate) fn times(self, n: usize) -> Self {
+		Frame { reps: n-1, ..self }
+	}
+
+	pub(crate) fn code(&self) -> &Code {
+		&self.code
+	}
+
+	pub(crate) fn levels(&self) -> usize {
+		self.levels
+	}
+
+	//invoked by a command, not the original input
+	pub(crate) fn is_invoked(&self) -> bool {
+		self.call.is_some()
+	}
+
+	//character index of the next command
+	pub(crate) fn pos(&self) -> usize {
+		self.pc
+	}
+
+	pub(crate) fn locate(&self, pos: usize) -> Location {
+		Site { code: Rc::clone(&self.code), source: Rc::clone(&self.source), pos }.locate()
+	}
+
+	//current repetition finished?
+	pub(crate) fn is_empty(&self) -> bool {
+		self.pc>=self.code.chars.len()
+	}
+
+	//all repetitions finished?
+	pub(crate) fn is_done(&self) -> bool {
+		self.is_empty() && self.reps==0
+	}
+
+	//start the next repetition if there is one
+	pub(crate) fn restart(&mut self) -> bool {
+		if self.reps==0 {
+			return false;
+		}
+		self.reps -= 1;
+		self.pc = 0;
+		true
+	}
+
+	//exit levels>0 invocations starting with the current one, returns how many are left to exit below this frame
+	pub(crate) fn quit(&mut self, levels: usize) -> usize {
+		self.pc = self.code.chars.len();
+		if levels==1 {
+			return 0;	//only the current repetition, 'X' goes on with the next one
+		}
+		self.reps = 0;	//invoker of 'X' is exited too
+		levels.saturating_sub(self.levels)
+	}
+
+	pub(crate) fn peek(&self) -> Option<char> {
+		self.code.chars.get(self.pc).copied()
+	}
+
+	//remaining commands
+	pub(crate) fn rest(&self) -> &[char] {
+		&self.code.chars[self.pc.min(self.code.chars.len())..]
+	}
+
+	pub(crate) fn pop(&mut self) -> Option<char> {
+		let c = self.peek()?;
+		self.pc += 1;
+		Some(c)
+	}
+
+	//undo pop() that returned c, does nothing after it returned None if c isn't the last character
+	pub(crate) fn unpop(&mut self, c: char) {
+		if self.pc>0 && self.code.chars[self.pc-1]==c {
+			self.pc -= 1;
+		}
+	}
+
+	//continue at character index pos
+	pub(crate) fn jump(&mut self, pos: usize) {
+		self.pc = pos;
+	}
+
+	//skip everything up to and including the next c, or the rest if there is none
+	pub(crate) fn skip_past(&mut self, c: char) {
+		self.pc = match self.rest().iter().position(|&r| r==c) {
+			Some(i) => self.pc+i+1,
+			None => self.code.chars.len(),
+		};
+	}
+}
+
+//invoke frame from the top one, which is replaced if it's finished (tail call optimization)
+pub(crate) fn call(frames: &mut Vec<Frame>, mut frame: Frame) {
+	if frames.last().is_some_and(Frame::is_done) {
+		let done = frames.pop().unwrap();
+		frame.levels += done.levels;
+		frame.tail = done.tail.or(done.call);
+	}
+	frames.push(frame);
+}
+
+//backtrace of a command that started at character index pos of the innermost frame
+pub(crate) fn backtrace(frames: &[Frame], pos: usize) -> Backtrace {
+	let location = match frames.last() {
+		Some(frame) => frame.locate(pos),
+		None => Location { source: Source::Input, line: 1, column: 1 },
+	};
+	let calls = frames.iter().rev()
+		.flat_map(|frame| frame.call.iter().chain(&frame.tail))
+		.map(|(cmd, site)| (*cmd, site.locate()))
+		.collect();
+	Backtrace { location, calls }
+}
@@ -0,0 +1,69 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use rug::Integer;
+use crate::{Interpreter, Obj};
+
+//compiled macros kept by an interpreter, the cache is dropped when it grows beyond this
+const CACHE_SIZE: usize = 1024;
+
+//literal that was parsed at some position of a macro
+#[derive(Debug)]
+struct Literal {
+	obj: Obj,
+	end: usize,	//index of the first character after it
+	env: Option<(Integer, u32)>,	//input base and working precision it was parsed with, None if they don't matter
+}
+
+//code ready for execution: indexable characters and the literals parsed so far, shared by every invocation of the same string
+#[derive(Debug)]
+pub(crate) struct Code {
+	pub(crate) text: Rc<str>,
+	pub(crate) chars: Box<[char]>,
+	literals: RefCell<HashMap<usize, Literal>>,	//by index of their first character
+}
+
+impl Code {
+	pub(crate) fn new(text: Rc<str>) -> Self {
+		Code {
+			chars: text.chars().collect(),
+			text,
+			literals: RefCell::new(HashMap::new()),
+		}
+	}
+
+	//byte index of character index pos in text
+	pub(crate) fn byte_pos(&self, pos: usize) -> usize {
+		self.text.char_indices().nth(pos).map_or(self.text.len(), |(i, _)| i)
+	}
+
+	//object and end of the literal starting at pos if it was parsed with the same environment before
+	pub(crate) fn literal(&self, pos: usize, ibase: &Integer, wprec: u32) -> Option<(Obj, usize)> {
+		let literals = self.literals.borrow();
+		let lit = literals.get(&pos)?;
+		match &lit.env {
+			Some((i, w)) if i!=ibase || *w!=wprec => None,
+			_ => Some((lit.obj.clone(), lit.end)),
+		}
+	}
+
+	//remember literal from pos to end for the next time, env as in Literal
+	pub(crate) fn set_literal(&self, pos: usize, end: usize, obj: Obj, env: Option<(Integer, u32)>) {
+		self.literals.borrow_mut().insert(pos, Literal { obj, end, env });
+	}
+}
+
+impl Interpreter {
+	//compiled form of a macro, only done once for every string
+	pub(crate) fn compile(&mut self, text: String) -> Rc<Code> {
+		if let Some(code) = self.compiled.get(text.as_str()) {
+			return Rc::clone(code);
+		}
+		if self.compiled.len()>=CACHE_SIZE {
+			self.compiled.clear();
+		}
+		let code = Rc::new(Code::new(text.into()));
+		self.compiled.insert(Rc::clone(&code.text), Rc::clone(&code));
+		code
+	}
+}
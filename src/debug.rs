@@ -0,0 +1,45 @@
+use crate::{Interpreter, Location, backtrace::Frame};
+
+/// Called before every command with the interpreter and its pending macros, see [`Interpreter::set_debug_hook`].
+pub type DebugHook = Box<dyn FnMut(&Interpreter, &CallStack)>;
+
+/// Macros that are being executed, from the original input (depth 1) to the innermost macro.
+pub struct CallStack<'a> {
+	pub(crate) frames: &'a [Frame],
+}
+
+impl CallStack<'_> {
+	/// Nesting depth, 1 while executing the original input. Macros replaced by tail calls are still counted.
+	pub fn depth(&self) -> usize {
+		self.frames.iter().map(Frame::levels).sum()
+	}
+
+	/// Command that is about to be executed.
+	pub fn next_cmd(&self) -> Option<char> {
+		self.frames.last().and_then(|f| f.peek())
+	}
+
+	/// Remaining commands of every macro, innermost first. There are fewer than [`depth`](Self::depth) after tail calls.
+	pub fn frames(&self) -> impl Iterator<Item = String> + '_ {
+		self.frames.iter().rev().map(|f| f.rest().iter().collect())
+	}
+
+	/// Location of the next command.
+	pub fn location(&self) -> Option<Location> {
+		self.frames.last().map(|f| f.locate(f.pos()))
+	}
+
+	/// Checks if the innermost macro was just invoked and is `mac`.
+	pub fn starts_macro(&self, mac: &str) -> bool {
+		self.frames.last().is_some_and(|f| f.is_invoked() && f.pos()==0 && &*f.code().text==mac)
+	}
+}
+
+impl Interpreter {
+	/// Sets a function that is called before every command, for tracing and debugging.
+	///
+	/// It can inspect the state and pause execution by blocking, setting the [interrupt flag](Self::interrupt_flag) stops execution.
+	pub fn set_debug_hook(&mut self, hook: Option<DebugHook>) {
+		self.debug = hook;
+	}
+}
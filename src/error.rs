@@ -0,0 +1,135 @@
+use rug::Integer;
+use std::fmt;
+use std::time::Duration;
+use crate::Obj;
+
+/// Everything that can go wrong while executing dc:im code.
+///
+/// Operands that caused the error are carried along in `args` (in canonical order), they are no longer on the stack.
+#[derive(Clone, Debug)]
+pub enum DcError {
+	/// Not enough objects on the main stack for a command.
+	InsufficientArgs { cmd: char },
+	/// The command can't be used on these operand types.
+	InvalidTypes { cmd: char, args: Vec<Obj> },
+	/// Mathematically undefined operation, like division by zero.
+	Arithmetic { cmd: char, args: Vec<Obj>, kind: ArithError },
+	/// An operand is outside of the range accepted by the command.
+	InvalidArgument { cmd: char, args: Vec<Obj>, reason: String },
+	/// A register command got neither a register name nor a valid DRS.
+	NoRegister { cmd: char },
+	/// Register has no object to read.
+	RegisterEmpty { cmd: char, reg: usize },
+	/// Malformed number or string input.
+	Parse { reason: String },
+	/// There is no constant or conversion factor with this name.
+	UnknownConstant { name: String },
+	/// Character isn't a command.
+	InvalidCommand { cmd: char },
+	/// Failed interaction with the OS (files, environment variables, OS commands).
+	System { cmd: char, reason: String },
+	/// The command needs a capability that is disabled by the sandbox.
+	Forbidden { cmd: char, what: String },
+	/// A resource limit was reached, always stops execution.
+	LimitExceeded { cmd: char, kind: LimitKind },
+	/// Execution was stopped from outside with the [interrupt flag](crate::Interpreter::interrupt_flag).
+	Interrupted,
+}
+
+/// Kinds of [`DcError::Arithmetic`].
+#[derive(Clone, Debug)]
+pub enum ArithError {
+	DivByZero,
+	ModZero,
+	NegativeRoot,
+	NonPositiveLog,
+	LogBase,
+	NoInverse(Integer, Integer),
+	AsinDomain,
+	AcosDomain,
+}
+
+/// Kinds of [`DcError::LimitExceeded`], with the limit that was reached.
+#[derive(Clone, Debug)]
+pub enum LimitKind {
+	Commands(u64),
+	Time(Duration),
+	StackDepth(usize),
+	ArrayLength(usize),
+	StringLength(usize),
+	Precision(u32),
+}
+
+impl fmt::Display for LimitKind {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			LimitKind::Commands(n) => write!(f, "more than {} commands executed", n),
+			LimitKind::Time(t) => write!(f, "execution took longer than {:?}", t),
+			LimitKind::StackDepth(n) => write!(f, "more than {} objects on the main stack", n),
+			LimitKind::ArrayLength(n) => write!(f, "register arrays can't be longer than {}", n),
+			LimitKind::StringLength(n) => write!(f, "strings can't be longer than {} bytes", n),
+			LimitKind::Precision(n) => write!(f, "working precision can't be higher than {}", n),
+		}
+	}
+}
+
+impl fmt::Display for ArithError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ArithError::DivByZero => write!(f, "Attempted division by zero"),
+			ArithError::ModZero => write!(f, "Attempted reduction mod 0"),
+			ArithError::NegativeRoot => write!(f, "Roots of negative numbers are not allowed"),
+			ArithError::NonPositiveLog => write!(f, "Logarithms of zero and negative numbers are not allowed"),
+			ArithError::LogBase => write!(f, "Logarithm base must be positive and not equal to 1"),
+			ArithError::NoInverse(a, m) => write!(f, "{} doesn't have an inverse mod {}", a, m),
+			ArithError::AsinDomain => write!(f, "Arc-sine of value outside [-1,1]"),
+			ArithError::AcosDomain => write!(f, "Arc-cosine of value outside [-1,1]"),
+		}
+	}
+}
+
+//messages are printed after "! " by the default handler
+impl fmt::Display for DcError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			DcError::InsufficientArgs { cmd: 'd' } => write!(f, "Nothing to duplicate"),
+			DcError::InsufficientArgs { cmd: 'r' } => write!(f, "Not enough objects to rotate"),
+			DcError::InsufficientArgs { cmd } => write!(f, "Insufficient arguments for command '{}'", cmd),
+			DcError::InvalidTypes { cmd, .. } => write!(f, "Invalid argument type(s) for command '{}'", cmd),
+			DcError::Arithmetic { kind, .. } => write!(f, "Arithmetic error: {}", kind),
+			DcError::InvalidArgument { reason, .. } => write!(f, "{}", reason),
+			DcError::NoRegister { cmd: '<'|'='|'>' } => write!(f, "No register name provided"),
+			DcError::NoRegister { .. } => write!(f, "No register number provided"),
+			DcError::RegisterEmpty { reg, .. } => write!(f, "Register {} is empty", reg),
+			DcError::Parse { reason } => write!(f, "{}", reason),
+			DcError::UnknownConstant { name } => write!(f, "Constant/conversion factor \"{}\" doesn't exist", name),
+			DcError::InvalidCommand { cmd } => write!(f, "Invalid command: {} (U+{:04X})", cmd, *cmd as u32),
+			DcError::System { reason, .. } => write!(f, "{}", reason),
+			DcError::Forbidden { what, .. } => write!(f, "{} is not allowed in sandbox mode", what),
+			DcError::LimitExceeded { kind, .. } => write!(f, "Limit exceeded: {}", kind),
+			DcError::Interrupted => write!(f, "Interrupted"),
+		}
+	}
+}
+
+impl DcError {
+	/// Exit code used by the dcim binary when this error stops execution, one per category.
+	pub fn exit_code(&self) -> i32 {
+		match self {
+			DcError::InsufficientArgs { .. } => 2,
+			DcError::InvalidTypes { .. } => 3,
+			DcError::Arithmetic { .. } => 4,
+			DcError::InvalidArgument { .. } => 5,
+			DcError::NoRegister { .. }|DcError::RegisterEmpty { .. } => 6,
+			DcError::Parse { .. } => 7,
+			DcError::UnknownConstant { .. } => 8,
+			DcError::InvalidCommand { .. } => 9,
+			DcError::System { .. } => 10,
+			DcError::Forbidden { .. } => 11,
+			DcError::LimitExceeded { .. } => 12,
+			DcError::Interrupted => 130,	//like termination by SIGINT
+		}
+	}
+}
+
+impl std::error::Error for DcError {}
@@ -0,0 +1,222 @@
+use rug::{Integer, Float, ops::Pow};
+use std::fmt::Write;
+use crate::{Interpreter, Obj, RegObj};
+
+/*
+	Exported scripts only use literals that are valid in the input base they're written for
+	and reproduce every number exactly, including its precision:
+	- the input base is first set with a base-independent expression made of (1), d and +
+	- letter commands are separated from literals, which may contain letters in input bases over 10
+	- every number is preceded by <precision>w if the precision changes
+	- integers and terminating fractions (in even input bases) are written as literals,
+	  everything else as <mantissa> 2 <exponent>^* or <mantissa> 2 <exponent>^/
+	- strings with unbalanced brackets are assembled from pieces and character codes
+*/
+
+impl Interpreter {
+	/// Creates a dc:im script that reconstructs the state when executed.
+	///
+	/// With `None`, the script recreates the main stack, all registers (including `S`-pushed layers and arrays), the parameter stack, working precision and DRS, assuming an empty interpreter.
+	/// With a list of register numbers, only those registers are recreated and the parameters of the executing interpreter are left unchanged.
+	pub fn export_script(&self, regs: Option<&[usize]>) -> String {
+		let ibase = self.envstk.last().unwrap().1.clone();
+		let mut sw = ScriptWriter { out: String::new(), ibase: ibase.clone(), wprec: None };
+		match regs {
+			None => {
+				writeln!(sw.out, "#dcim state export").unwrap();
+				//bottom context in a base-independent way, further ones with base 10 after {
+				let (k, i, o) = &self.envstk[0];
+				writeln!(sw.out, "{}i {} k {} o", indep_lit(i), int_lit(k, i), int_lit(o, i)).unwrap();
+				for (k, i, o) in &self.envstk[1..] {
+					let dec = Integer::from(10);
+					writeln!(sw.out, "{{{} k {} o {} i", int_lit(k, &dec), int_lit(o, &dec), int_lit(i, &dec)).unwrap();
+				}
+				for obj in &self.mstk {
+					sw.obj(obj);
+					sw.out.push('\n');
+				}
+				for (ri, reg) in &self.regs {
+					sw.register(*ri, reg);
+				}
+				writeln!(sw.out, "{} w", int_lit(&Integer::from(self.wprec), &ibase)).unwrap();
+				if let Some(drs) = self.drs {
+					writeln!(sw.out, "{},", int_lit(&Integer::from(drs), &ibase)).unwrap();
+				}
+			},
+			Some(regs) => {
+				writeln!(sw.out, "#dcim register export").unwrap();
+				writeln!(sw.out, "{{{}iW", indep_lit(&ibase)).unwrap();	//keep parameters and working precision of the executing interpreter
+				for &ri in regs {
+					sw.register(ri, self.reg(ri));
+				}
+				writeln!(sw.out, "w}}").unwrap();
+			},
+		}
+		sw.out
+	}
+}
+
+struct ScriptWriter {
+	out: String,
+	ibase: Integer,	//input base of all literals
+	wprec: Option<u32>,	//working precision at this point of the script, None if unknown
+}
+
+impl ScriptWriter {
+	fn int(&self, int: &Integer) -> String {
+		int_lit(int, &self.ibase)
+	}
+
+	//pushes one object
+	fn obj(&mut self, obj: &Obj) {
+		match obj {
+			Obj::Str(s) => {
+				let lit = self.string(s);
+				self.out.push_str(&lit);
+			},
+			Obj::Num(n) => {
+				if self.wprec!=Some(n.prec()) {
+					let prec = self.int(&Integer::from(n.prec()));
+					write!(self.out, "{} w ", prec).unwrap();
+					self.wprec = Some(n.prec());
+				}
+				let lit = self.num(n);
+				self.out.push_str(&lit);
+			},
+		}
+	}
+
+	//exact number literal or expression, evaluated with the number's precision
+	fn num(&self, n: &Float) -> String {
+		let two = self.int(&Integer::from(2));
+		let huge = format!("{} {}^", two, self.int(&(Integer::from(1)<<62)));	//overflows to infinity
+		let minus = format!("{}*", self.int(&Integer::from(-1)));
+		if n.is_nan() {
+			format!("{}d-", huge)
+		}
+		else if n.is_infinite() {
+			if n.is_sign_negative() {format!("{}{}", huge, minus)} else {huge}
+		}
+		else if n.is_zero() {
+			let zero = self.int(&Integer::ZERO);
+			if n.is_sign_negative() {format!("{} {}", zero, minus)} else {zero}
+		}
+		else {
+			let (mut man, mut exp) = n.to_integer_exp().unwrap();	//finite and nonzero
+			let tz = man.find_one(0).unwrap();
+			man >>= tz;	//shortest mantissa
+			exp += tz as i32;
+			if exp>=0 {
+				if exp<=64 {
+					self.int(&(man << exp as u32))
+				}
+				else {
+					format!("{} {} {}^*", self.int(&man), two, self.int(&Integer::from(exp)))
+				}
+			}
+			else {
+				let k = exp.unsigned_abs();
+				if self.ibase.is_even() && self.ibase<=36 && k<=1024 {
+					//m/2^k = m*(B/2)^k/B^k, so it has exactly k digits after the separator
+					let digits = (man.clone().abs() * (self.ibase.clone()/2u32).pow(k)).to_string_radix(self.ibase.to_i32().unwrap());
+					let digits = format!("{:0>width$}", digits, width = k as usize + 1);
+					let (ipart, fpart) = digits.split_at(digits.len()-k as usize);
+					format!("{}{}{}.{}", if self.ibase>10 {"'"} else {""}, if man<0 {"_"} else {""}, ipart, fpart.trim_end_matches('0'))
+				}
+				else {
+					format!("{} {} {}^/", self.int(&man), two, self.int(&Integer::from(k)))
+				}
+			}
+		}
+	}
+
+	//string literal, assembled from pieces if brackets are unbalanced
+	fn string(&self, s: &str) -> String {
+		let mut nest: usize = 0;
+		let mut plain = true;
+		for c in s.chars() {
+			match c {
+				'[' => {nest += 1;},
+				']' => {
+					if nest==0 {plain = false; break;}
+					nest -= 1;
+				},
+				_ => {},
+			}
+		}
+		if plain && nest==0 {
+			return format!("[{}]", s);
+		}
+		//appended to an empty string piece by piece
+		let mut res = String::from("[]");
+		let mut piece = String::new();
+		for c in s.chars() {
+			if c=='['||c==']' {
+				if !piece.is_empty() {
+					write!(res, "[{}]+", piece).unwrap();
+					piece.clear();
+				}
+				write!(res, " {} a+", self.int(&Integer::from(c as u32))).unwrap();
+			}
+			else {
+				piece.push(c);
+			}
+		}
+		if !piece.is_empty() {
+			write!(res, "[{}]+", piece).unwrap();
+		}
+		res
+	}
+
+	//recreates all layers of a register, the first one with s to replace existing contents
+	fn register(&mut self, ri: usize, reg: &[RegObj]) {
+		for (li, ro) in reg.iter().enumerate() {
+			self.obj(&ro.o);
+			let name = self.reg_name(ri, if li==0 {'s'} else {'S'});
+			writeln!(self.out, " {}", name).unwrap();
+			for ai in (0..ro.a.len()).rev() {	//highest index first, extends the array only once
+				self.obj(&ro.a[ai]);
+				let name = self.reg_name(ri, ':');
+				writeln!(self.out, " {} {}", self.int(&Integer::from(ai)), name).unwrap();
+			}
+		}
+	}
+
+	//command with register name, uses the DRS for characters that can't be written as-is
+	fn reg_name(&self, ri: usize, cmd: char) -> String {
+		match u32::try_from(ri).ok().and_then(char::from_u32) {
+			Some(c) if !c.is_control() && !c.is_whitespace() => format!("{}{}", cmd, c),
+			_ => format!("{},{}", self.int(&Integer::from(ri)), cmd),
+		}
+	}
+}
+
+//integer literal for input base ibase
+fn int_lit(int: &Integer, ibase: &Integer) -> String {
+	if *ibase<=36 {
+		let digits = int.to_string_radix(ibase.to_i32().unwrap()).replacen('-', "_", 1);
+		if *ibase>10 {format!("'{}", digits)} else {digits}
+	}
+	else {	//any-base input
+		let mut digits = Vec::new();
+		let mut rest = int.clone().abs();
+		while rest>0 {
+			let (quot, rem) = rest.div_rem_euc(ibase.clone());
+			digits.push(rem.to_string());
+			rest = quot;
+		}
+		if digits.is_empty() {digits.push("0".into());}
+		digits.reverse();
+		format!("({}{})", if *int<0 {"-"} else {""}, digits.join(" "))
+	}
+}
+
+//positive integer literal that's valid in any input base: binary Horner scheme with (1), d and +
+fn indep_lit(int: &Integer) -> String {
+	let bits = int.to_string_radix(2);
+	let mut res = String::from("(1)");
+	for bit in bits.chars().skip(1) {
+		res.push_str(if bit=='1' {"d+(1)+"} else {"d+"});
+	}
+	res
+}
@@ -0,0 +1,133 @@
+/// Commands that are followed by a register name unless the DRS is used.
+const REG_CMDS: &str = "sSlL:;jJhHZF<=>";
+
+/// Removes all #comments from a script, like executing it would.
+///
+/// `#` only starts a comment outside of strings, any-base numbers and register names, so `[# of items: ]n` and `s#` are kept intact.
+/// ```
+/// assert_eq!(dcim::strip_comments("[#1]p #print\n5s# l#p"), "[#1]p \n5s# l#p");
+/// ```
+pub fn strip_comments(script: &str) -> String {
+	let mut res = String::with_capacity(script.len());
+	let mut chars = script.chars();
+	let mut drs = false;	//DRS was set with ',' and not used yet
+	while let Some(c) = chars.next() {
+		match c {
+			//strings with nesting
+			'[' => {
+				res.push(c);
+				let mut nest: usize = 1;
+				for c in chars.by_ref() {
+					res.push(c);
+					if c=='[' {nest += 1;}
+					if c==']' {nest -= 1;}
+					if nest==0 {break;}
+				}
+			},
+			//any-base numbers
+			'(' => {
+				res.push(c);
+				for c in chars.by_ref() {
+					res.push(c);
+					if c==')' {break;}
+				}
+			},
+			//comment until end of line
+			'#' => {
+				if chars.by_ref().any(|c| c=='\n') {
+					res.push('\n');
+				}
+			},
+			',' => {
+				res.push(c);
+				drs = true;
+			},
+			_ if REG_CMDS.contains(c) => {
+				res.push(c);
+				if drs {
+					drs = false;
+				}
+				else if let Some(name) = chars.next() {
+					res.push(name);
+				}
+			},
+			_ => {
+				res.push(c);
+			},
+		}
+	}
+	res
+}
+
+//construct that is still open at the end of an incomplete script
+enum Open {
+	Nothing,
+	String(usize),	//innermost string, starting at this byte index
+	Number,	//any-base number
+}
+
+//skips comments, any-base numbers and register names like strip_comments
+fn scan_open(script: &str) -> Open {
+	let mut starts = Vec::new();
+	let mut chars = script.char_indices();
+	let mut drs = false;
+	while let Some((i, c)) = chars.next() {
+		if !starts.is_empty() {	//only nesting matters inside strings
+			match c {
+				'[' => {starts.push(i+1);},
+				']' => {starts.pop();},
+				_ => {},
+			}
+			continue;
+		}
+		match c {
+			'[' => {starts.push(i+1);},
+			'(' => {
+				let closed = chars.by_ref().any(|(_, c)| c==')');
+				if !closed {
+					return Open::Number;
+				}
+			},
+			'#' => {chars.by_ref().any(|(_, c)| c=='\n');},
+			',' => {drs = true;},
+			_ if REG_CMDS.contains(c) => {
+				if drs {
+					drs = false;
+				}
+				else {
+					chars.next();
+				}
+			},
+			_ => {},
+		}
+	}
+	match starts.last() {
+		Some(start) => Open::String(*start),
+		None => Open::Nothing,
+	}
+}
+
+/// Finds the innermost string that is still open at the end of an incomplete script and returns the byte index where its content starts.
+///
+/// Comments, any-base numbers and register names are skipped like in [`strip_comments`], so `s[` doesn't open a string.
+/// ```
+/// assert_eq!(dcim::open_string("5[in"), Some(2));
+/// assert_eq!(dcim::open_string("[[a]x[b"), Some(6));
+/// assert_eq!(dcim::open_string("[in]\" s[ #[c"), None);
+/// ```
+pub fn open_string(script: &str) -> Option<usize> {
+	match scan_open(script) {
+		Open::String(start) => Some(start),
+		_ => None,
+	}
+}
+
+/// Checks if a script ends inside a string or any-base number, so more input is needed before executing it.
+/// ```
+/// assert!(dcim::is_unfinished("[d1-d0<x\n"));
+/// assert!(dcim::is_unfinished("(1 2"));
+/// assert!(!dcim::is_unfinished("[]p #[\n(1)"));
+/// ```
+pub fn is_unfinished(script: &str) -> bool {
+	!matches!(scan_open(script), Open::Nothing)
+}
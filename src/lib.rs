@@ -0,0 +1,2471 @@
+//! dc:im interpreter library: everything needed to run dc:im code inside other Rust programs.
+//!
+//! All state lives in an [`Interpreter`], so several independent instances can exist at once.
+//! ```no_run
+//! let mut dc = dcim::Interpreter::new();
+//! dc.exec("2 3+p");
+//! ```
+
+use rug::{Integer, integer::Order, Complete, Float, float::{Round, Constant}, ops::Pow, rand::RandState};
+use std::io::stdin;
+use std::time::{SystemTime, Duration, Instant};
+use std::cmp::Ordering;
+use std::collections::{HashSet, HashMap, BTreeMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, atomic::AtomicBool};
+use std::rc::Rc;
+
+mod error;
+pub use error::{DcError, ArithError, LimitKind};
+mod output;
+pub use output::{Output, Stdout, Stderr, Buffer};
+mod sandbox;
+pub use sandbox::{Sandbox, FileAccess};
+mod limits;
+pub use limits::Limits;
+mod state;
+pub use state::Snapshot;
+mod export;
+mod lexer;
+mod debug;
+mod backtrace;
+mod compile;
+use compile::Code;
+pub use backtrace::{Backtrace, Location, Source};
+use backtrace::Frame;
+pub use debug::{DebugHook, CallStack};
+pub use lexer::{strip_comments, open_string, is_unfinished};
+
+//environment parameter defaults
+fn kdef() -> Integer { Integer::from(-1) }
+fn idef() -> Integer { Integer::from(10) }
+fn odef() -> Integer { Integer::from(10) }
+
+/// Basic object on a dc stack, in a register or an array.
+#[derive(Clone, Debug)]
+pub enum Obj {
+	Num(Float),
+	Str(String),
+}
+
+/// Objects are equal if they are identical: numbers need the same precision, the sign of zero matters and NaN equals NaN.
+impl PartialEq for Obj {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Obj::Num(a), Obj::Num(b)) => a.prec()==b.prec() && a.as_ord()==b.as_ord(),
+			(Obj::Str(a), Obj::Str(b)) => a==b,
+			_ => false,
+		}
+	}
+}
+
+/// Register object, may have a dynamic array.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegObj {
+	pub o: Obj,			//principal object
+	pub a: Vec<Obj>,	//associated array
+}
+
+impl Obj {
+	/// Number variant, shorthand constructor.
+	pub fn n(number: Float) -> Self {
+		Obj::Num(number)
+	}
+	/// String variant, shorthand constructor.
+	pub fn s(string: String) -> Self {
+		Obj::Str(string)
+	}
+
+	/// Is it a string?
+	pub fn is_str(&self) -> bool {
+		matches!(self, Obj::Str(_))
+	}
+
+	/// Number, `None` if it's a string.
+	pub fn as_num(&self) -> Option<&Float> {
+		match self {
+			Obj::Num(n) => Some(n),
+			Obj::Str(_) => None,
+		}
+	}
+
+	/// String, `None` if it's a number.
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			Obj::Num(_) => None,
+			Obj::Str(s) => Some(s),
+		}
+	}
+
+	//contents of objects whose type was verified by check_t
+	fn num(&self) -> &Float {
+		self.as_num().expect("type checked")
+	}
+	fn into_num(self) -> Float {
+		match self {
+			Obj::Num(n) => n,
+			Obj::Str(_) => unreachable!("type checked"),
+		}
+	}
+	fn str(&self) -> &str {
+		self.as_str().expect("type checked")
+	}
+	fn into_str(self) -> String {
+		match self {
+			Obj::Num(_) => unreachable!("type checked"),
+			Obj::Str(s) => s,
+		}
+	}
+}
+
+/// Names of all constants and units that can be looked up with `"`, without scale prefixes or power suffixes.
+pub const CONSTANT_NAMES: &[&str] = &[
+	"e", "pi", "gamma", "phi", "deg", "°", "gon", "grad",
+	"c", "hbar", "G", "qe", "NA", "kB", "u", "lp", "tp", "mp", "Tp",
+	"in", "ft", "yd", "m", "fur", "mi", "nmi", "AU", "ly", "pc",
+	"ac", "acre", "l", "ifloz", "ipt", "iqt", "igal", "ibu", "ibsh", "ufldr", "tsp", "tbsp", "ufloz", "upt", "uqt", "ugal", "bbl", "udpt", "udqt", "udgal", "ubu", "ubsh", "dbbl",
+	"ct", "oz", "lb", "kg", "st", "t",
+	"s", "min", "h", "d", "w",
+	"J", "cal", "Pa", "atm", "psi",
+	"time", "timens", "pid", "author",
+];
+
+const INT_ORD_DEF: (Integer, Ordering) = (Integer::ZERO, Ordering::Equal);	//default tuple for to_integer_round().unwrap_or()
+
+/// Receives every error that occurs during execution and where it happened.
+pub type ErrorHandler = Box<dyn FnMut(&DcError, &Backtrace)>;
+
+/// Default error handler, prints the message to stderr.
+///
+/// The location and macro invocations are only printed for files and macros, errors in directly entered code are obvious.
+pub fn print_error(e: &DcError, bt: &Backtrace) {
+	if bt.calls.is_empty() && bt.location.source==Source::Input {
+		eprintln!("! {}", e);
+	}
+	else {
+		eprintln!("! {}: {}", bt.location, e);
+		for (cmd, loc) in &bt.calls {
+			eprintln!("  in macro invoked by '{}' at {}", cmd, loc);
+		}
+	}
+}
+
+/// Complete state of one dc:im instance.
+pub struct Interpreter {
+	mstk: Vec<Obj>,	//main stack
+	regs: BTreeMap<usize, Vec<RegObj>>,	//registers by number, only the ones that were written to
+	ro_buf: RegObj,	//buffer for j/J/h/H
+	envstk: Vec<(Integer, Integer, Integer)>,	//stores (k,i,o) tuples, used by '{' and '}'
+	wprec: u32,	//working precision (rug Float mantissa length)
+	drs: Option<usize>,	//direct register selector, None if invalid
+	rng: RandState<'static>,
+	handler: ErrorHandler,	//where errors are reported
+	errors: usize,	//amount of errors reported so far
+	strict: bool,	//stop execution on first error?
+	abort: Option<DcError>,	//error that stops execution in strict mode
+	reported: Vec<DcError>,	//errors of the current command, passed to the handler with their location after it
+	out: Box<dyn Output>,	//where printing commands write to
+	sandbox: Sandbox,	//what may be accessed outside of the interpreter
+	limits: Limits,	//resource limits per exec call
+	search_path: Vec<PathBuf>,	//directories for relative file names of '&' and 'M'
+	included: HashSet<PathBuf>,	//files executed by '&' and 'M' so far
+	interrupt: Arc<AtomicBool>,	//set from outside to stop execution
+	debug: Option<DebugHook>,	//called before every command
+	compiled: HashMap<Rc<str>, Rc<Code>>,	//macros by their content, executed without parsing them again
+	generation: u64,	//incremented by everything that may modify the state
+}
+
+impl Default for Interpreter {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Interpreter {
+	/// Creates an interpreter with default parameters and empty stack and registers.
+	pub fn new() -> Self {
+		let wprec = 256;
+		//initialize RNG with system time (* PID for a bit less predictability)
+		let mut rng = RandState::new();
+		rng.seed(&(Integer::from(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or(Duration::MAX).as_nanos()) * std::process::id()));
+		Interpreter {
+			mstk: Vec::new(),
+			regs: BTreeMap::new(),
+			ro_buf: RegObj {
+				a: Vec::new(),
+				o: Obj::n(Float::with_val(wprec, 0))
+			},
+			envstk: vec![(kdef(), idef(), odef())],
+			wprec,
+			drs: None,
+			rng,
+			handler: Box::new(print_error),
+			errors: 0,
+			strict: false,
+			abort: None,
+			reported: Vec::new(),
+			out: Box::new(Stdout),
+			sandbox: Sandbox::default(),
+			limits: Limits::default(),
+			search_path: Vec::new(),
+			included: HashSet::new(),
+			interrupt: Arc::new(AtomicBool::new(false)),
+			debug: None,
+			compiled: HashMap::new(),
+			generation: 0,
+		}
+	}
+
+	/// Replaces the error handler. Execution continues normally after every error.
+	pub fn set_error_handler(&mut self, handler: ErrorHandler) {
+		self.handler = handler;
+	}
+
+	/// Replaces the destination of all printing commands.
+	pub fn set_output(&mut self, out: Box<dyn Output>) {
+		self.out = out;
+	}
+
+	/// Enables or disables strict mode, where the first error stops execution.
+	pub fn set_strict(&mut self, strict: bool) {
+		self.strict = strict;
+	}
+
+	/// Is strict mode enabled?
+	pub fn is_strict(&self) -> bool {
+		self.strict
+	}
+
+	/// Restricts access to OS commands, files, environment variables and process-killing constants.
+	pub fn set_sandbox(&mut self, sandbox: Sandbox) {
+		self.sandbox = sandbox;
+	}
+
+	/// Current sandbox settings.
+	pub fn sandbox(&self) -> &Sandbox {
+		&self.sandbox
+	}
+
+	/// Sets the resource limits that apply to every following call of [`exec`](Self::exec).
+	pub fn set_limits(&mut self, limits: Limits) {
+		self.limits = limits;
+	}
+
+	/// Current resource limits.
+	pub fn limits(&self) -> &Limits {
+		&self.limits
+	}
+
+	/// Sets the directories that `&` and `M` search (in order) for relative file names that don't exist in the working directory.
+	pub fn set_search_path(&mut self, dirs: Vec<PathBuf>) {
+		self.search_path = dirs;
+	}
+
+	//resolves a file name for '&' and 'M'
+	fn find_file(&self, name: &str) -> PathBuf {
+		let path = Path::new(name);
+		if path.is_relative() && !path.exists() {
+			if let Some(found) = self.search_path.iter().map(|dir| dir.join(path)).find(|p| p.exists()) {
+				return found;
+			}
+		}
+		path.to_path_buf()
+	}
+
+	/// Flag that stops the running [`exec`](Self::exec) call with [`DcError::Interrupted`] when set, for example from a signal handler.
+	///
+	/// Stack and registers are kept. The flag is cleared when the interruption happens, so the interpreter can be used again right away.
+	pub fn interrupt_flag(&self) -> Arc<AtomicBool> {
+		Arc::clone(&self.interrupt)
+	}
+
+	/// Counter that changes whenever the state saved by [`snapshot`](Self::snapshot) may have been modified.
+	///
+	/// If it's the same as before executing something, the state is unchanged. Commands that only print or do nothing don't change it.
+	pub fn generation(&self) -> u64 {
+		self.generation
+	}
+
+	/// Amount of errors that occurred since this interpreter was created.
+	pub fn error_count(&self) -> usize {
+		self.errors
+	}
+
+	/// Passes an error that happened outside of [`exec`](Self::exec), like invalid input prepared for a script, to the error handler and counts it.
+	pub fn report_error(&mut self, e: &DcError, location: Location) {
+		self.errors += 1;
+		(self.handler)(e, &Backtrace { location, calls: Vec::new() });
+	}
+
+	//queue error for the handler, mark for stopping if in strict mode
+	fn report(&mut self, e: DcError) {
+		self.errors += 1;
+		if self.strict && self.abort.is_none() {
+			self.abort = Some(e.clone());
+		}
+		self.reported.push(e);
+	}
+
+	//pass errors of the command that started at character index pos of frame depth-1 to the handler
+	fn flush(&mut self, frames: &[Frame], depth: usize, pos: usize) {
+		if !self.reported.is_empty() {
+			let bt = backtrace::backtrace(&frames[..depth.min(frames.len())], pos);
+			for e in std::mem::take(&mut self.reported) {
+				(self.handler)(&e, &bt);
+			}
+		}
+	}
+
+	//report exceeded limit, always stops execution
+	fn stop(&mut self, cmd: char, kind: LimitKind) {
+		let e = DcError::LimitExceeded { cmd, kind };
+		self.report(e.clone());
+		if self.abort.is_none() {
+			self.abort = Some(e);
+		}
+	}
+
+	//checks if a string of this length may be created
+	fn check_len(&mut self, cmd: char, len: usize) -> bool {
+		match self.limits.string_length {
+			Some(max) if len>max => {
+				self.stop(cmd, LimitKind::StringLength(max));
+				false
+			},
+			_ => true,
+		}
+	}
+
+	//checks if a register array may be extended to this length
+	fn check_arr(&mut self, cmd: char, len: usize) -> bool {
+		match self.limits.array_length {
+			Some(max) if len>max => {
+				self.stop(cmd, LimitKind::ArrayLength(max));
+				false
+			},
+			_ => true,
+		}
+	}
+
+	//extends the top array of register ri to at least len objects (initialized with default objects), false if that isn't possible
+	fn extend_arr(&mut self, cmd: char, args: &[&Obj], ri: usize, len: usize) -> bool {
+		let old = self.reg(ri).last().unwrap().a.len();
+		if len<=old {
+			return true;
+		}
+		if !self.check_arr(cmd, len) {
+			return false;
+		}
+		let arr = &mut self.regs.entry(ri).or_default().last_mut().unwrap().a;
+		if arr.try_reserve_exact(len-old).is_err() {	//allocation failures would abort the process
+			self.report(DcError::InvalidArgument { cmd, args: args.iter().map(|&o| o.clone()).collect(), reason: format!("Not enough memory for an array of length {}", len) });
+			return false;
+		}
+		arr.resize(len, Obj::n(Float::with_val(self.wprec, 0)));
+		true
+	}
+
+	//checks if there are enough arguments for a command (defines adicity)
+	//not used by niladics
+	fn check_n(&mut self, op: char) -> bool {
+		let n = self.mstk.len();
+		if match op {
+			//triadic
+			'|' => n>=3,
+
+			//dyadic
+			'+'|'-'|'*'|'/'|'^'|'V'|'G'|'%'|'~'|'@'|':'|'='|'<'|'>'|'X'|'r'|'E' => n>=2,
+
+			//monadic unless specified
+			_ => n>=1,
+		}
+		{ true }
+		else {
+			self.report(DcError::InsufficientArgs { cmd: op });
+			false
+		}
+	}
+
+	//checks if a command can be used on provided arguments
+	//args: operands that would be used (in canonical order)
+	fn check_t(&mut self, op: char, args: &[&Obj]) -> bool {
+		let a = args.first().is_some_and(|o| o.is_str());	//types, true iff string
+		let b = args.get(1).is_some_and(|o| o.is_str());
+		let c = args.get(2).is_some_and(|o| o.is_str());
+		if match op {
+			//'+' can also concatenate strings
+			'+' => (!a&&!b)||(a&&b),
+
+			//string manipulation, store into array
+			'-'|'*'|'/'|'~'|':' => !b,
+
+			//read file by name, get env variable, execute os command, save or load state
+			'&'|'M'|'$'|'\\'|'b'|'B' => a,
+
+			//convert both ways, constant lookup by string name or convert number to string, execute macros, get log or string length
+			'a'|'A'|'"'|'x'|'g' => true,
+
+			//auto-macro
+			'X' => a&&!b,
+
+			//export registers to file
+			'E' => a&&b,
+
+			//all other ops can only have numbers
+			_ => !a&&!b&&!c,
+		}
+		{ true }
+		else {
+			self.report(DcError::InvalidTypes { cmd: op, args: args.iter().map(|o| (*o).clone()).collect() });
+			false
+		}
+	}
+
+	/// Main stack, top is last.
+	pub fn stack(&self) -> &[Obj] {
+		&self.mstk
+	}
+
+	/// Mutable access to the main stack.
+	pub fn stack_mut(&mut self) -> &mut Vec<Obj> {
+		self.generation += 1;
+		&mut self.mstk
+	}
+
+	/// Register `ri`, top is last. Every number is a valid register, unused ones are empty.
+	pub fn register(&self, ri: usize) -> &[RegObj] {
+		self.reg(ri)
+	}
+
+	/// Mutable access to register `ri`.
+	pub fn register_mut(&mut self, ri: usize) -> &mut Vec<RegObj> {
+		self.generation += 1;
+		self.regs.entry(ri).or_default()
+	}
+
+	//registers are only stored once something is written to them
+	fn reg(&self, ri: usize) -> &[RegObj] {
+		self.regs.get(&ri).map_or(&[], |reg| reg.as_slice())
+	}
+
+	/// Makes script arguments available in register `$`: the amount of arguments as the top object, the arguments themselves as strings in its array.
+	pub fn set_args(&mut self, args: &[String]) {
+		self.generation += 1;
+		*self.regs.entry('$' as usize).or_default() = vec![RegObj {
+			o: Obj::n(Float::with_val(self.wprec, args.len())),
+			a: args.iter().cloned().map(Obj::s).collect(),
+		}];
+	}
+
+	/// Parses a number in the current input base (like standard number input, but `-` is also accepted as negative sign) without executing anything.
+	pub fn parse_number(&self, input: &str) -> Result<Float, DcError> {
+		let ibase = &self.envstk.last().unwrap().1;
+		if *ibase>36 {
+			return Err(DcError::Parse { reason: "Any-base input must be used for input bases over 36".to_string() });
+		}
+		let numstr = input.trim().replace('_', "-");
+		match Float::parse_radix(&numstr, ibase.to_i32().unwrap()) {
+			Ok(res) => Ok(Float::with_val(self.wprec, res)),
+			Err(error) => Err(DcError::Parse { reason: format!("Unable to parse number \"{}\": {}", input, error) }),
+		}
+	}
+
+	/// Current working precision in bits.
+	pub fn wprec(&self) -> u32 {
+		self.wprec
+	}
+
+	/// Current (output precision, input base, output base).
+	pub fn params(&self) -> (&Integer, &Integer, &Integer) {
+		let (k, i, o) = self.envstk.last().unwrap();
+		(k, i, o)
+	}
+
+	/// Register number selected with `,` for the next register command (or the exit code of `q`), `None` if there is none.
+	pub fn drs(&self) -> Option<usize> {
+		self.drs
+	}
+
+	/// Formats an object like `f` prints it: strings in brackets, numbers with the current output base and precision.
+	///
+	/// Numbers that would be longer than the [string length limit](Limits::string_length) are only described.
+	pub fn format_obj(&self, obj: &Obj) -> String {
+		match obj {
+			Obj::Str(s) => format!("[{}]", s),
+			Obj::Num(n) => {
+				let (k, _, o) = self.envstk.last().unwrap();
+				match self.limits.string_length {
+					Some(max) if flt_str_len(n, o, k)>max => format!("<number longer than {} characters>", max),
+					_ => flt_to_str(n.clone(), o.clone(), k.clone()),
+				}
+			},
+		}
+	}
+
+	//number with the current output parameters, None if it may exceed the string length limit
+	fn num_str(&mut self, cmd: char, num: &Float) -> Option<String> {
+		let (k, _, o) = self.envstk.last().unwrap();
+		let len = flt_str_len(num, o, k);
+		if self.check_len(cmd, len) {
+			Some(flt_to_str(num.clone(), self.envstk.last().unwrap().2.clone(), self.envstk.last().unwrap().0.clone()))
+		}
+		else {
+			None
+		}
+	}
+
+	//library of constants and unit conversion factors, keep CONSTANT_NAMES up to date
+	//unless specified, unit factors are based on the most prevalent international standard units for their respective quantities
+	//ex: "in" (inch) returns 0.0254, thus executing 20[in]"* converts 20 inches to meters (0.508)
+	fn constants(&self, prec: u32, key: String) -> Result<Float, DcError> {
+		match key.as_str() {
+			/*----------------------------
+				MATHEMATICAL CONSTANTS
+			----------------------------*/
+			"e" => {Ok(Float::with_val(prec, 1).exp())}
+			"pi" => {Ok(Float::with_val(prec, Constant::Pi))}
+			"gamma" => {Ok(Float::with_val(prec, Constant::Euler))}
+			"phi" => {Ok((Float::with_val(prec, 5).sqrt()+1)/2)}
+			"deg"|"°" => {Ok(Float::with_val(prec, Constant::Pi)/180)}
+			"gon"|"grad" => {Ok(Float::with_val(prec, Constant::Pi)/200)}
+			/*------------------------
+				PHYSICAL CONSTANTS
+			------------------------*/
+			"c" => {Ok(Float::with_val(prec, 299792458))}
+			"hbar" => {Ok(sci_to_flt(prec, 662607015, -42)/(2*Float::with_val(prec, Constant::Pi)))}
+			"G" => {Ok(sci_to_flt(prec, 6674, -3))}
+			"qe" => {Ok(sci_to_flt(prec, 1602176634, -28))}
+			"NA" => {Ok(sci_to_flt(prec, 602214076, 31))}
+			"kB" => {Ok(sci_to_flt(prec, 1380649, -29))}
+			"u" => {Ok(sci_to_flt(prec, 1660539066, -36))}
+			"lp" => {Ok(sci_to_flt(prec, 16162, -39))}
+			"tp" => {Ok(sci_to_flt(prec, 5391, -47))}
+			"mp" => {Ok(sci_to_flt(prec, 21764, -12))}
+			"Tp" => {Ok(sci_to_flt(prec, 14167, 28))}
+			/*------------------
+				LENGTH UNITS
+			------------------*/
+			"in" => {Ok(sci_to_flt(prec, 254, -4))}
+			"ft" => {Ok(self.constants(prec, "in".to_string()).unwrap()*12)}
+			"yd" => {Ok(self.constants(prec, "ft".to_string()).unwrap()*3)}
+			"m" => {Ok(Float::with_val(prec, 1))}
+			"fur" => {Ok(self.constants(prec, "ft".to_string()).unwrap()*660)}
+			"mi" => {Ok(self.constants(prec, "ft".to_string()).unwrap()*5280)}
+			"nmi" => {Ok(Float::with_val(prec, 1852))}
+			"AU" => {Ok(Float::with_val(prec, 149597870700i64))}
+			"ly" => {Ok(Float::with_val(prec, 9460730472580800i64))}
+			"pc" => {Ok(Float::with_val(prec, 96939420213600000i64)/Float::with_val(prec, Constant::Pi))}
+			/*-------------------------------
+				   AREA & VOLUME UNITS
+				with no length equivalent
+			-------------------------------*/
+			"ac"|"acre" => {Ok(sci_to_flt(prec, 40468564224, -7))}
+			"l" => {Ok(Float::with_val(prec, 10).pow(-3))}
+			"ifloz" => {Ok(sci_to_flt(prec, 284130625, -13))}
+			"ipt" => {Ok(self.constants(prec, "ifloz".to_string()).unwrap()*20)}
+			"iqt" => {Ok(self.constants(prec, "ifloz".to_string()).unwrap()*40)}
+			"igal" => {Ok(self.constants(prec, "ifloz".to_string()).unwrap()*160)}
+			"ibu"|"ibsh" => {Ok(self.constants(prec, "ifloz".to_string()).unwrap()*1280)}
+			"ufldr" => {Ok(sci_to_flt(prec, 36966911953125, -19))}
+			"tsp" => {Ok(self.constants(prec, "ufldr".to_string()).unwrap()/3*4)}
+			"tbsp" => {Ok(self.constants(prec, "ufldr".to_string()).unwrap()*4)}
+			"ufloz" => {Ok(self.constants(prec, "ufldr".to_string()).unwrap()*8)}
+			"upt" => {Ok(self.constants(prec, "ufloz".to_string()).unwrap()*16)}
+			"uqt" => {Ok(self.constants(prec, "ufloz".to_string()).unwrap()*32)}
+			"ugal" => {Ok(self.constants(prec, "ufloz".to_string()).unwrap()*128)}
+			"bbl" => {Ok(self.constants(prec, "ugal".to_string()).unwrap()*42)}
+			"udpt" => {Ok(sci_to_flt(prec, 5506104713575, -16))}
+			"udqt" => {Ok(self.constants(prec, "udpt".to_string()).unwrap()*2)}
+			"udgal" => {Ok(self.constants(prec, "udpt".to_string()).unwrap()*8)}
+			"ubu"|"ubsh" => {Ok(self.constants(prec, "udpt".to_string()).unwrap()*64)}
+			"dbbl" => {Ok(sci_to_flt(prec, 115627123584, -12))}
+			/*----------------
+				MASS UNITS
+			----------------*/
+			"ct" => {Ok(sci_to_flt(prec, 2, -4))}
+			"oz" => {Ok(sci_to_flt(prec, 28349523125, -12))}
+			"lb" => {Ok(self.constants(prec, "oz".to_string()).unwrap()*16)}
+			"kg" => {Ok(Float::with_val(prec, 1))}
+			"st" => {Ok(self.constants(prec, "lb".to_string()).unwrap()*14)}
+			"t" => {Ok(self.constants(prec, "lb".to_string()).unwrap()*2240)}
+			/*----------------
+				TIME UNITS
+			----------------*/
+			"s" => {Ok(Float::with_val(prec, 1))}
+			"min" => {Ok(Float::with_val(prec, 60))}
+			"h" => {Ok(self.constants(prec, "min".to_string()).unwrap()*60)}
+			"d" => {Ok(self.constants(prec, "h".to_string()).unwrap()*24)}
+			"w" => {Ok(self.constants(prec, "d".to_string()).unwrap()*7)}
+			/*-----------------
+				OTHER UNITS
+			-----------------*/
+			"J" => {Ok(Float::with_val(prec, 1))}
+			"cal" => {Ok(sci_to_flt(prec, 4184, -3))}
+			"Pa" => {Ok(Float::with_val(prec, 1))}
+			"atm" => {Ok(Float::with_val(prec, 101325))}
+			"psi" => {Ok(sci_to_flt(prec, 6894757293168, -9))}
+			/*------------------------------
+				SPECIAL VALUES/FUNCTIONS
+			------------------------------*/
+			"time" => {Ok(Float::with_val(prec, SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or(Duration::ZERO).as_secs()))}
+			"timens" => {Ok(Float::with_val(prec, SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or(Duration::ZERO).as_nanos()))}
+			"pid" => {Ok(Float::with_val(prec, std::process::id()))}
+			"abort"|"crash"|"panic" if !self.sandbox.process_exit => {Err(DcError::Forbidden { cmd: '"', what: format!("Constant \"{}\"", key) })}
+			"abort" => {std::process::abort();}
+			"crash" => {self.constants(prec, "crash".to_string())}	//stack overflow through recursion
+			"panic" => {std::panic::panic_any(
+				if let Some(ptr) = self.mstk.last() {
+					if ptr.is_str() {ptr.str().to_string()} else {"Manual panic".to_string()}}
+				else {"Manual panic".to_string()});}
+			"author" => {Ok(Float::with_val(prec, 43615))}	//why not
+			_ => {Err(DcError::UnknownConstant { name: key })}
+		}
+	}
+
+	//CORE EXECUTION ENGINE
+	/// Executes a string of dc commands on this interpreter's state.
+	///
+	/// Returns `Ok(Some(code))` if execution was ended by `q`, the caller decides what to do with it.
+	/// In strict mode, the first error stops execution and is returned as `Err` (after being passed to the error handler).
+	pub fn exec(&mut self, input: &str) -> Result<Option<i32>, DcError> {
+		self.exec_source(input, Source::Input)
+	}
+
+	/// Like [`exec`](Self::exec), but error locations refer to `name` (usually the file name of a script).
+	pub fn exec_named(&mut self, input: &str, name: &str) -> Result<Option<i32>, DcError> {
+		self.exec_source(input, Source::Named(name.to_string()))
+	}
+
+	fn exec_source(&mut self, input: &str, source: Source) -> Result<Option<i32>, DcError> {
+		let mut cmdstk: Vec<Frame> = Vec::new();	//stack of command strings to execute with their origin, enables pseudorecursive macro calls
+		let mut inv = false;	//invert next comparison
+		let start = Instant::now();
+		let mut count: u64 = 0;	//amount of commands executed
+		self.abort = None;	//may be left over if a previous call panicked
+		self.reported.clear();
+		if !input.is_empty() {	//loop expects contents, do nothing if none provided
+			cmdstk.push(Frame::new(Rc::new(Code::new(input.into())), source));
+		}
+		while !cmdstk.is_empty() {	//last().unwrap() is guaranteed to not panic within
+			if let Some(mut hook) = self.debug.take() {	//can't be borrowed while the hook inspects self
+				hook(self, &CallStack { frames: &cmdstk });
+				self.debug = Some(hook);
+			}
+			let depth = cmdstk.len();	//where the command comes from, for error locations
+			let pos = cmdstk.last().unwrap().pos();
+	
+			let mut cmd = cmdstk.last_mut().unwrap().pop().unwrap();	//isolate first character as command
+			if !matches!(cmd, ' '|'\t'|'\n'|'\r'|'p'|'f'|'F'|'#'|'!'|'q') {	//everything else may modify the state
+				self.generation += 1;
+			}
+
+			count += 1;
+			if let Some(max) = self.limits.commands {
+				if count>max {
+					self.stop(cmd, LimitKind::Commands(max));
+				}
+			}
+			if let Some(max) = self.limits.time {
+				if start.elapsed()>max {
+					self.stop(cmd, LimitKind::Time(max));
+				}
+			}
+			if self.interrupt.swap(false, std::sync::atomic::Ordering::Relaxed) {
+				self.report(DcError::Interrupted);
+				self.abort = Some(DcError::Interrupted);	//takes precedence, cmdstk is dropped
+			}
+			self.flush(&cmdstk, depth, pos);
+			if let Some(e) = self.abort.take() {
+				return Err(e);
+			}
+
+			//literals are only parsed once at every position, depending on the input base for numbers
+			let mut cached = match cmd {
+				'0'..='9'|'.'|'_'|'\''|'@'|'('|'[' => cmdstk.last().unwrap().code().literal(pos, &self.envstk.last().unwrap().1, self.wprec),
+				_ => None,
+			};
+
+			//defines behavior of all commands
+			match cmd {
+				//literal that was parsed before, continue after it
+				_ if cached.is_some() => {
+					let (obj, end) = cached.take().unwrap();
+					self.mstk.push(obj);
+					cmdstk.last_mut().unwrap().jump(end);
+				},
+
+				/*------------------
+					OBJECT INPUT
+				------------------*/
+				//standard number input, force with single quote to use letters
+				'0'..='9'|'.'|'_'|'\''|'@' => {
+					if self.envstk.last().unwrap().1>36 {
+						self.report(DcError::Parse { reason: "Any-base input must be used for input bases over 36".to_string() });
+					}
+					else {
+						let mut numstr = String::new();	//gets filled with number to be parsed later
+						let mut frac = false;	//'.' has already occurred
+						let mut neg = false;	//'_' has already occurred
+						let mut alpha = false;	//letters are used
+						if cmd == '\'' {
+							alpha = true;
+							cmd = cmdstk.last_mut().unwrap().pop().unwrap_or('\0');
+						}
+						//keep adding to numstr until number is finished
+						'STDNUM_FINISHED: loop {
+							//numbers, periods and exponential notation
+							if cmd.is_ascii_digit()||cmd == '.'||cmd == '@' {
+								if cmd == '.' { if frac { break 'STDNUM_FINISHED; } else { frac = true; } } //break on encountering second '.'
+								if cmd == '@' { neg = false; }	//allow for second negative sign in exponent
+								numstr.push(cmd);						
+							}
+							//'_' needs to be replaced with '-'
+							else if cmd == '_' {
+								if neg { break 'STDNUM_FINISHED; } else { neg = true; } //break on encountering second '_'
+								numstr.push('-');
+							}
+							//parse letters if number is prefixed with quote
+							else if cmd.is_ascii_alphabetic() {
+								if alpha {
+									numstr.push(cmd);							
+								}
+								else {
+									break 'STDNUM_FINISHED;
+								}
+							}
+							else {
+								break 'STDNUM_FINISHED;
+							}
+							cmd = cmdstk.last_mut().unwrap().pop().unwrap_or('\0');
+						}
+						cmdstk.last_mut().unwrap().unpop(cmd);	//restore first char that isn't part of the number
+						if numstr.starts_with('@') { numstr.insert(0, '1') }	//add implied 1 before exponential marker
+						if numstr.starts_with('.')||numstr.starts_with("-.") { numstr = numstr.replace('.', "0."); }	//add implied zero before fractional separator
+						if numstr.ends_with('.')||numstr.ends_with('-')||numstr.is_empty() { numstr.push('0'); }	//add implied zero at end
+						match Float::parse_radix(numstr.clone(), self.envstk.last().unwrap().1.to_i32().unwrap()) {		
+							Ok(res) => {
+								let obj = Obj::n(Float::with_val(self.wprec, res));
+								let frame = cmdstk.last().unwrap();
+								frame.code().set_literal(pos, frame.pos(), obj.clone(), Some((self.envstk.last().unwrap().1.clone(), self.wprec)));
+								self.mstk.push(obj);
+							},
+							Err(error) => {
+								self.report(DcError::Parse { reason: format!("Unable to parse number \"{}\": {}", numstr, error) });
+							},
+						}
+					}
+				},
+
+				//any-base number input
+				'(' => {
+					let mut num = Integer::from(0);	//resulting number
+					if cmdstk.last().unwrap().is_empty() {
+						self.mstk.push(Obj::n(Float::with_val(self.wprec, num)));	//default to 0 if on end of input
+					}
+					else {
+						let ibase = self.envstk.last().unwrap().1.clone();
+						let mut dig = String::new();	//digit being parsed
+						let mut neg = false;	//number negative?
+						let mut frac = false;	//fractional separator has occurred
+						let mut scale = Integer::from(1);	//scale to divide by, for non-integers
+						let mut exp = false;	//exponential symbol has occurred
+						let mut end_dig = false;	//finish digit before reading on
+						'CANCEL_ABNUM: loop {
+							cmd = if std::mem::take(&mut end_dig) {' '} else {cmdstk.last_mut().unwrap().pop().unwrap_or(')')};	//get next character, finish number if not possible
+							match cmd {
+								'0'..='9' => {
+									dig.push(cmd);	//add numerals to digit
+								},
+								'-'|'_' => {
+									if neg {
+										self.report(DcError::Parse { reason: "Unable to parse any-base number: more than one negative sign".to_string() });
+										cmdstk.last_mut().unwrap().skip_past(')');	//remove rest of erroneous number
+										break;
+									}
+									neg = true;
+								},
+								'.' => {
+									if frac {
+										self.report(DcError::Parse { reason: "Unable to parse any-base number: more than one fractional separator".to_string() });
+										cmdstk.last_mut().unwrap().skip_past(')');	//remove rest of erroneous number
+										break;
+									}
+									frac = true;
+									end_dig = true;	//end digit in next iteration
+								},
+								'@' => {
+									exp = true;
+									end_dig = true;	//end digit in next iteration, exponent handled by finalizer
+								},
+								' '|'\n'|')' => {	//if digit or whole number is finished, digits may be separated by line breaks
+									let digint = if dig.clone().is_empty() {Integer::ZERO} else {Integer::parse(dig.clone()).unwrap().complete()};	//parse digit, default to 0
+									if digint >= ibase {
+										self.report(DcError::Parse { reason: format!("Unable to parse any-base number: digit '{}' is too high for base {}", digint, ibase) });
+										if cmd==')' {break;}
+										else {
+											cmdstk.last_mut().unwrap().skip_past(')');	//remove rest of erroneous number
+											break;
+										}
+									}
+									num *= ibase.clone();	//add digit to number: multiply old contents by radix...
+									num += digint;	//... and add new digit
+									dig.clear();
+									if frac {
+										scale *= ibase.clone();	//if fractional part has started, make scale keep up
+									}
+									let escale =	//power applied to input base for exponential notation
+									if exp {	//if exponential part has begun
+										let mut epart = String::new();
+										let mut eneg = false;
+										while !cmdstk.last().unwrap().is_empty() {
+											cmd = cmdstk.last_mut().unwrap().pop().unwrap();
+											match cmd {
+												'0'..='9' => {
+													epart.push(cmd);
+												},
+												'-'|'_' => {
+													if eneg {
+														self.report(DcError::Parse { reason: "Unable to parse any-base number: more than one negative sign in exponent".to_string() });
+														cmdstk.last_mut().unwrap().skip_past(')');	//remove rest of erroneous number
+														break 'CANCEL_ABNUM;
+													}
+													epart.insert(0, '-');
+													eneg = true;
+												},
+												')' => {
+													break;
+												},
+												_ => {
+													self.report(DcError::Parse { reason: format!("Unable to parse any-base number: invalid character '{}' in exponent", cmd) });
+													cmdstk.last_mut().unwrap().skip_past(')');	//remove rest of erroneous number
+													break 'CANCEL_ABNUM;
+												},
+											}
+										}
+										match epart.parse::<Integer>() {
+											Ok(eint) => eint,
+											Err(_) => {
+												self.report(DcError::Parse { reason: "Unable to parse any-base number: missing exponent".to_string() });
+												break 'CANCEL_ABNUM;
+											},
+										}
+									}
+									else {
+										Integer::from(0)
+									};
+									if cmd==')' {	//if number finished, push to stack
+										if scale>1 {
+											scale /= ibase.clone();	//correct off-by-one error
+										}
+										let obj = Obj::n(Float::with_val(self.wprec, num * if neg {-1} else {1}) / scale
+											* Float::with_val(self.wprec, &ibase).pow(escale));
+										let frame = cmdstk.last().unwrap();
+										frame.code().set_literal(pos, frame.pos(), obj.clone(), Some((ibase, self.wprec)));
+										self.mstk.push(obj);
+										break;
+									}
+								},
+								_ => {
+									self.report(DcError::Parse { reason: format!("Invalid character in any-base number: '{}'", cmd) });
+									cmdstk.last_mut().unwrap().skip_past(')');	//remove rest of erroneous number
+									break;
+								},
+							}
+						}
+					}
+				},
+
+				//string input
+				'[' => {
+					let mut res = String::new();	//result string
+					let mut nest: usize = 1;	//nesting level
+					cmd = cmdstk.last_mut().unwrap().pop().unwrap_or('\0');	//overwrite opening bracket, null if nothing left
+					loop {
+						res.push(cmd);
+						if cmd == '[' { nest+=1; }
+						if cmd == ']' { nest-=1; }
+						if nest==0 {	//string finished
+							res.pop();	//remove closing bracket
+							let frame = cmdstk.last().unwrap();
+							frame.code().set_literal(pos, frame.pos(), Obj::s(res.clone()), None);
+							self.mstk.push(Obj::s(res));
+							break;
+						}
+						if cmdstk.last().unwrap().is_empty() {	//only reached on improper string
+							self.report(DcError::Parse { reason: format!("Unable to parse string \"[{}\": missing closing bracket", res) });
+							break;
+						}
+						else {cmd = cmdstk.last_mut().unwrap().pop().unwrap();}
+					}
+				},
+				/*--------------
+					PRINTING
+				--------------*/
+				//print top with newline
+				'p' => {
+					if !self.mstk.is_empty() {
+						if self.mstk.last().unwrap().is_str() {
+							writeln!(self.out, "[{}]", self.mstk.last().unwrap().str());
+						}
+						else {
+							let num = self.mstk.last().unwrap().num().clone();
+							if let Some(s) = self.num_str(cmd, &num) {
+								writeln!(self.out, "{}", s);
+							}
+						}
+					}
+				},
+
+				//print full stack top to bottom
+				'f' => {
+					for i in (0..self.mstk.len()).rev() {
+						let line = match &self.mstk[i] {
+							Obj::Str(s) => format!("[{}]", s),
+							Obj::Num(n) => {
+								let num = n.clone();
+								let Some(s) = self.num_str(cmd, &num) else {break;};
+								s
+							},
+						};
+						writeln!(self.out, "{}", line);
+					}
+				},
+
+				//pop and print without newline
+				'n' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if a.is_str() {
+							write!(self.out, "{}", a.str());
+							self.out.flush();
+						}
+						else if let Some(s) = self.num_str(cmd, a.num()) {
+							write!(self.out, "{}", s);
+							self.out.flush();
+						}
+					}
+				},
+
+				//pop and print with newline
+				'P' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if a.is_str() {
+							writeln!(self.out, "{}", a.str());
+						}
+						else if let Some(s) = self.num_str(cmd, a.num()) {
+							writeln!(self.out, "{}", s);
+						}
+					}
+				},
+
+				//print register
+				'F' => {
+					if cmdstk.last().unwrap().is_empty()&&self.drs.is_none() {
+						self.report(DcError::NoRegister { cmd });
+					}
+					else {
+						let ri = if let Some(drs) = self.drs.take() {
+							drs
+						}
+						else {
+							cmdstk.last_mut().unwrap().pop().unwrap() as usize
+						};
+						if !self.reg(ri).is_empty(){
+							'layers: for i in (0..self.reg(ri).len()).rev() {
+								if self.regs[&ri][i].o.is_str() {
+									writeln!(self.out, "[{}]", self.regs[&ri][i].o.str());
+								}
+								else {
+									let num = self.regs[&ri][i].o.num().clone();
+									let Some(s) = self.num_str(cmd, &num) else {break 'layers;};
+									writeln!(self.out, "{}", s);
+								}
+								if !self.regs[&ri][i].a.is_empty() {
+									let maxwidth = self.regs[&ri][i].a.len().to_string().len();	//length of longest index number
+									for ai in 0..self.regs[&ri][i].a.len() {
+										if self.regs[&ri][i].a[ai].is_str() {
+											writeln!(self.out, "\t{:>maxwidth$}: [{}]", ai, self.regs[&ri][i].a[ai].str());
+										}
+										else {
+											let num = self.regs[&ri][i].a[ai].num().clone();
+											let Some(s) = self.num_str(cmd, &num) else {break 'layers;};
+											writeln!(self.out, "\t{:>maxwidth$}: {}", ai, s);
+										}
+									}
+								}
+							}
+						}
+					}
+				},
+				/*----------------
+					ARITHMETIC
+				----------------*/
+				//add or concatenate strings
+				'+' => {
+					if self.check_n(cmd) {
+						let b = self.mstk.pop().unwrap();
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a, &b]) {
+							//concat strings
+							if a.is_str() {
+								if self.check_len(cmd, a.str().len()+b.str().len()) {
+									self.mstk.push(Obj::s(a.into_str() + b.str()));
+								}
+							}
+							//add numbers
+							else {
+								self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num() + b.into_num())));
+							}
+						}
+					}
+				},
+
+				//subtract or remove chars from string
+				'-' => {
+					if self.check_n(cmd) {
+						let b = self.mstk.pop().unwrap();
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a, &b]) {
+							//remove b chars from string a
+							if a.is_str() {
+								let mut newstr = a.str().chars().collect::<Vec<char>>();
+								let int = b.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;	//extract b, keep for checking if negative
+								if let Some(mut num) = &int.abs_ref().complete().to_usize() {
+									if num>newstr.len() { num = newstr.len(); }	//account for too large b
+									if int<0 { newstr.reverse(); }	//if negative, reverse to remove from front
+									newstr.truncate(newstr.len()-num);
+									if int<0 { newstr.reverse(); }	//undo reversal
+									self.mstk.push(Obj::s(newstr.iter().collect::<String>()));
+								}
+								else {
+									self.report(DcError::InvalidArgument { cmd, args: vec![a, b], reason: format!("Cannot possibly remove {} characters from a string", int) });
+								}
+							}
+							//subtract numbers
+							else {
+								self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num() - b.into_num())));
+							}
+						}
+					}
+				},
+
+				//multiply or repeat/invert string
+				'*' => {
+					if self.check_n(cmd) {
+						let b = self.mstk.pop().unwrap();
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a, &b]) {
+							//repeat string a b times
+							if a.is_str() {
+								let newstr = a.into_str();
+								let int = b.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;	//extract b, keep for checking if negative
+								if let Some((num, len)) = int.abs_ref().complete().to_usize().and_then(|num| Some((num, num.checked_mul(newstr.len())?))) {
+									if self.check_len(cmd, len) {
+										let mut rep = String::new();
+										if rep.try_reserve_exact(len).is_ok() {	//allocation failures would abort the process
+											if !newstr.is_empty() {
+												for _ in 0..num { rep.push_str(&newstr); }
+											}
+											if int<0 { rep = rev_str(rep); }	//if b is negative, invert string
+											self.mstk.push(Obj::s(rep));
+										}
+										else {
+											self.report(DcError::InvalidArgument { cmd, args: vec![Obj::s(newstr), b], reason: format!("Not enough memory to repeat a string {} times", int) });
+										}
+									}
+								}
+								else {
+									self.report(DcError::InvalidArgument { cmd, args: vec![Obj::s(newstr), b], reason: format!("Cannot possibly repeat a string {} times", int) });
+								}
+							}
+							//multiply numbers
+							else {
+								self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num() * b.into_num())));
+							}
+						}
+					}
+				},
+			
+				//divide or shorten string to length
+				'/' => {
+					if self.check_n(cmd) {
+						let b = self.mstk.pop().unwrap();
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a, &b]) {
+							//shorten string a to length b
+							if a.is_str() {
+								let mut newstr = a.str().chars().collect::<Vec<char>>();
+								let int = b.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;	//extract b, keep for checking if negative
+								if let Some(num) = &int.abs_ref().complete().to_usize() {
+									if int<0 { newstr.reverse(); }	//if negative, reverse to remove from front
+									newstr.truncate(*num);
+									if int<0 { newstr.reverse(); }	//undo reversal
+									self.mstk.push(Obj::s(newstr.iter().collect::<String>()));
+								}
+								else {
+									self.report(DcError::InvalidArgument { cmd, args: vec![a, b], reason: format!("Cannot possibly shorten a string to {} characters", int) });
+								}
+							}
+							//divide numbers
+							else {
+								if *b.num()==0 {
+									self.report(DcError::Arithmetic { cmd, args: vec![a, b], kind: ArithError::DivByZero });
+								}
+								else {
+									self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num() / b.into_num())));
+								}
+							}
+						}
+					}
+				},
+
+				//modulo, integers only
+				'%' => {
+					if self.check_n(cmd) {
+						let b = self.mstk.pop().unwrap();
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a, &b]) {
+							let ia = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							let ib = b.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							if ib==0 {
+								self.report(DcError::Arithmetic { cmd, args: vec![a, b], kind: ArithError::ModZero });
+							}
+							else {
+								self.mstk.push(Obj::n(Float::with_val(self.wprec, ia % ib)));
+							}
+						}
+					}
+				},
+
+				//euclidean division or split string
+				'~' => {
+					if self.check_n(cmd) {
+						let b = self.mstk.pop().unwrap();
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a, &b]) {
+							if a.is_str() {
+								let int = b.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+								if let Some(mut idx) = &int.to_usize() {
+									let cvec = a.str().chars().collect::<Vec<char>>();
+									if idx>cvec.len() { idx=cvec.len(); }	//if too large, split at max index to preserve signature
+									self.mstk.push(Obj::s(cvec[0..idx].iter().collect::<String>()));
+									self.mstk.push(Obj::s(cvec[idx..].iter().collect::<String>()));
+								}
+								else {
+									self.report(DcError::InvalidArgument { cmd, args: vec![a, b], reason: format!("Cannot possibly split a string at character {}", int) });
+								}
+							}
+							else {
+								let ia = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+								let ib = b.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+								if ib==0 {
+									self.report(DcError::Arithmetic { cmd, args: vec![a, b], kind: ArithError::ModZero });
+								}
+								else {
+									let (quot, rem)=ia.div_rem_euc(ib);
+									self.mstk.push(Obj::n(Float::with_val(self.wprec, quot)));
+									self.mstk.push(Obj::n(Float::with_val(self.wprec, rem)));
+								}
+							}
+						}
+					}
+				},
+
+				//exponentiation
+				'^' => {
+					if self.check_n(cmd) {
+						let b = self.mstk.pop().unwrap();
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a, &b]) {
+							if *a.num()<0&&b.num().clone().abs()<1{
+								self.report(DcError::Arithmetic { cmd, args: vec![a, b], kind: ArithError::NegativeRoot });
+							}
+							else {
+								self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num().pow(b.into_num()))));
+							}
+						}
+					}
+				},
+
+				//modular exponentiation, integers only
+				'|' => {
+					if self.check_n(cmd) {
+						let c = self.mstk.pop().unwrap();
+						let b = self.mstk.pop().unwrap();
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a, &b, &c]) {
+							let ia = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							let ib = b.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							let ic = c.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							if ic==0 {
+								self.report(DcError::Arithmetic { cmd, args: vec![a, b, c], kind: ArithError::ModZero });
+							}
+							else {
+								if let Ok(res) = ia.clone().pow_mod(&ib, &ic) {
+									self.mstk.push(Obj::n(Float::with_val(self.wprec, res)));
+								}
+								else {
+									self.report(DcError::Arithmetic { cmd, args: vec![a, b, c], kind: ArithError::NoInverse(ia, ic) });
+								}
+							}
+						}
+					}
+				},
+
+				//square root
+				'v' => {
+					if self.check_n(cmd){
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							if *a.num()<0 {
+								self.report(DcError::Arithmetic { cmd, args: vec![a], kind: ArithError::NegativeRoot });
+							}
+							else {
+								self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num().sqrt())));
+							}
+						}
+					}
+				},
+
+				//bth root
+				'V' => {
+					if self.check_n(cmd){
+						let b = self.mstk.pop().unwrap();
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a, &b]) {
+							if *a.num()<0&&b.num().clone().abs()>1{
+								self.report(DcError::Arithmetic { cmd, args: vec![a, b], kind: ArithError::NegativeRoot });
+							}
+							else {
+								self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num().pow(b.into_num().recip()))));
+							}
+						}
+					}
+				},
+
+				//length of string or natural logarithm
+				'g' => {
+					if self.check_n(cmd){
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							if a.is_str() {
+								self.mstk.push(Obj::n(Float::with_val(self.wprec, a.str().chars().count())));
+							}
+							else {
+								if *a.num()<=0 {
+									self.report(DcError::Arithmetic { cmd, args: vec![a], kind: ArithError::NonPositiveLog });
+								}
+								else {
+									self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num().ln())));
+								}
+							}
+						}
+					}
+				},
+
+				//base b logarithm
+				'G' => {
+					if self.check_n(cmd){
+						let b = self.mstk.pop().unwrap();
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a, &b]) {
+							if *a.num()<=0 {
+								self.report(DcError::Arithmetic { cmd, args: vec![a, b], kind: ArithError::NonPositiveLog });
+							}
+							else if *b.num()==1||*b.num()<=0{
+								self.report(DcError::Arithmetic { cmd, args: vec![a, b], kind: ArithError::LogBase });
+							}
+							else {
+								self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num().ln()/b.into_num().ln())));
+							}
+						}
+					}
+				},
+
+				//sine
+				'u' => {
+					if self.check_n(cmd){
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num().sin())));
+						}
+					}
+				},
+
+				//cosine
+				'y' => {
+					if self.check_n(cmd){
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num().cos())));
+						}
+					}
+				},
+
+				//tangent
+				't' => {
+					if self.check_n(cmd){
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num().tan())));
+						}
+					}
+				},
+
+				//arc-sine
+				'U' => {
+					if self.check_n(cmd){
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							if a.num().clone().abs()>1 {
+								self.report(DcError::Arithmetic { cmd, args: vec![a], kind: ArithError::AsinDomain });
+							}
+							else {
+								self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num().asin())));
+							}
+						}
+					}
+				},
+
+				//arc-cosine
+				'Y' => {
+					if self.check_n(cmd){
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							if a.num().clone().abs()>1 {
+								self.report(DcError::Arithmetic { cmd, args: vec![a], kind: ArithError::AcosDomain });
+							}
+							else {
+								self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num().acos())));
+							}
+						}
+					}
+				},
+
+				//arc-tangent
+				'T' => {
+					if self.check_n(cmd){
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							self.mstk.push(Obj::n(Float::with_val(self.wprec, a.into_num().atan())));
+						}
+					}
+				},
+
+				//random integer [0;a)
+				'N' => {
+					if self.check_n(cmd){
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							let int = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							if int<=0 {
+								self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: "Upper bound for random value must be above 0".to_string() });
+							}
+							else {
+								self.mstk.push(Obj::n(Float::with_val(self.wprec, int.random_below(&mut self.rng))));
+							}
+						}
+					}
+				},
+
+				//constant/conversion factor lookup or convert number to string
+				'"' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							if a.is_str() {	//constant lookup
+								match a.str().matches(' ').count() {
+									0 => {	//normal lookup
+										let mut name = a.str().to_string();
+										let mut scale = String::new();
+										while name.starts_with(|c: char| c.is_ascii_digit()||c=='-') {
+											scale.push(name.remove(0));	//extract scale prefix
+										}
+										if scale.is_empty() {scale.push('0');}
+
+										let mut power = String::new();
+										while name.ends_with(|c: char| c.is_ascii_digit()) {
+											power.insert(0, name.pop().unwrap());	//extract power suffix
+										}
+										if power.is_empty() {power.push('1');}
+
+										match (self.constants(self.wprec, name), scale.parse::<Integer>()) {
+											(Ok(res), Ok(scale)) => {
+												self.mstk.push(Obj::n(Float::with_val(self.wprec, (res*Float::with_val(self.wprec, scale).exp10())
+														.pow(power.parse::<Integer>().unwrap_or_default()))));	//power suffix is only digits
+											},
+											(Err(e), _) => {
+												self.report(e);
+											},
+											(_, Err(_)) => {
+												self.report(DcError::InvalidArgument { cmd, args: vec![a.clone()], reason: format!("Invalid scale prefix \"{}\"", scale) });
+											},
+										}
+									},
+									1 => {	//conversion shorthand, everything is like the 0 case but twice
+										let (from, to) = a.str().split_once(' ').unwrap();
+										let mut sfrom = String::from(from);	//convert from this
+										let mut sto = String::from(to);	//to this
+
+										let mut kfrom = String::new();
+										while sfrom.starts_with(|c: char| c.is_ascii_digit()||c=='-') {
+											kfrom.push(sfrom.remove(0));	//extract scale prefix
+										}
+										if kfrom.is_empty() {kfrom.push('0');}
+										let mut pfrom = String::new();
+										while sfrom.ends_with(|c: char| c.is_ascii_digit()) {
+											pfrom.insert(0, sfrom.pop().unwrap());	//extract power suffix
+										}
+										if pfrom.is_empty() {pfrom.push('1');}
+
+										let mut kto = String::new();
+										while sto.starts_with(|c: char| c.is_ascii_digit()||c=='-') {
+											kto.push(sto.remove(0));	//extract scale prefix
+										}
+										if kto.is_empty() {kto.push('0');}
+										let mut pto = String::new();
+										while sto.ends_with(|c: char| c.is_ascii_digit()) {
+											pto.insert(0, sto.pop().unwrap());	//extract power suffix
+										}
+										if pto.is_empty() {pto.push('1');}
+
+										match (self.constants(self.wprec, sfrom.to_string()), self.constants(self.wprec, sto.to_string())) {
+											(Ok(nfrom), Ok(nto)) => {
+												if let (Ok(ifrom), Ok(ito)) = (kfrom.parse::<Integer>(), kto.parse::<Integer>()) {
+													self.mstk.push(Obj::n(Float::with_val(self.wprec,
+															(nfrom*Float::with_val(self.wprec, ifrom).exp10())
+																.pow(pfrom.parse::<Integer>().unwrap_or_default())/	//power suffixes are only digits
+															(nto*Float::with_val(self.wprec, ito).exp10())
+																.pow(pto.parse::<Integer>().unwrap_or_default()))));
+												}
+												else {
+													self.report(DcError::InvalidArgument { cmd, args: vec![a.clone()], reason: format!("Invalid scale prefix in \"{}\"", a.str()) });
+												}
+											},
+											(Err(e), _)|(_, Err(e)) => {
+												self.report(e);
+											},
+										}
+									},
+									_ => {
+										self.report(DcError::InvalidArgument { cmd, args: vec![a.clone()], reason: format!("Too many spaces in constant lookup/unit conversion string \"{}\"", a.str()) });
+									},
+								}
+							}
+							else if let Some(s) = self.num_str(cmd, a.num()) {	//"print" number to string
+								self.mstk.push(Obj::s(s));
+							}
+						}
+					}
+				},
+				/*------------------------
+					STACK MANIPULATION
+				------------------------*/
+				//clear stack
+				'c' => {
+					self.mstk.clear();
+				},
+
+				//remove top a objects from stack
+				'C' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							let int = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							if let Some(mut num) = int.to_usize() {
+								if num>self.mstk.len() { num = self.mstk.len(); }	//limit clear count
+								self.mstk.truncate(self.mstk.len()-num);
+							}
+							else {
+								self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: format!("Cannot possibly remove {} objects from the main stack", int) });
+							}
+						}
+					}
+				},
+
+				//duplicate top of stack
+				'd' => {
+					if self.check_n(cmd) {
+						self.mstk.extend_from_within(self.mstk.len()-1..);
+					}
+				},
+
+				//duplicate top a objects
+				'D' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							let int = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							if let Some(num) = int.to_usize() {
+								if num<=self.mstk.len() {
+									self.mstk.extend_from_within(self.mstk.len()-num..);
+								}
+								else {
+									self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: "Not enough objects to duplicate".to_string() });
+								}
+							}
+							else {
+								self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: format!("Cannot possibly duplicate {} objects", int) });
+							}
+						}
+					}
+				},
+
+				//swap top 2 objects
+				'r' => {
+					if self.check_n(cmd) {
+						let len = self.mstk.len();
+						self.mstk.swap(len-2, len-1);
+					}
+				},
+
+				//rotate top a objects
+				'R' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							let mut int = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							if int==0 { int = Integer::from(1); }	//replace 0 with effective no-op
+							if let Some(num) = int.clone().abs().to_usize() {
+								if num<=self.mstk.len() {
+									let len = self.mstk.len();
+									if int<0 {
+										self.mstk[len-num..].rotate_left(1);	//if negative, rotate left/down
+									}
+									else {
+										self.mstk[len-num..].rotate_right(1);	//right/up otherwise
+									}
+								}
+								else {
+									self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: "Not enough objects to rotate".to_string() });
+								}
+							}
+							else {
+								self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: format!("Cannot possibly rotate {} objects", int.abs()) });
+							}
+						}
+					}
+				},
+
+				//push stack depth
+				'z' => {
+					self.mstk.push(Obj::n(Float::with_val(self.wprec, self.mstk.len())));
+				},
+				/*----------------------------
+					ENVIRONMENT PARAMETERS
+				----------------------------*/
+				//set output precision
+				'k' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							let int = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							if int>=-1 {
+								//every fractional digit may be printed
+								if int<0 || self.check_len(cmd, int.to_usize().unwrap_or(usize::MAX)) {
+									self.envstk.last_mut().unwrap().0 = int;
+								}
+							}
+							else {
+								self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: "Output precision must be at least -1".to_string() });
+							}
+						}
+					}
+				},
+
+				//set input base
+				'i' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							let int = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							if int>=2 {
+								self.envstk.last_mut().unwrap().1 = int;
+							}
+							else {
+								self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: "Input base must be at least 2".to_string() });
+							}
+						}
+					}
+				},
+
+				//set output base
+				'o' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							let int = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							if int>=2 {
+								self.envstk.last_mut().unwrap().2 = int;
+							}
+							else {
+								self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: "Output base must be at least 2".to_string() });
+							}
+						}
+					}
+				},
+
+				//set working precision
+				'w' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							let int = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							if let Some(max) = self.limits.wprec.filter(|max| int>*max) {
+								self.stop(cmd, LimitKind::Precision(max));
+							}
+							else if int>=1 && int<=u32::MAX {
+								self.wprec = int.to_u32().unwrap();
+							}
+							else {
+								self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: format!("Working precision must be between {} and {} (inclusive)", 1, u32::MAX) });
+							}
+						}
+					}
+				},
+
+				//push output precision
+				'K' => {
+					self.mstk.push(Obj::n(Float::with_val(self.wprec, self.envstk.last().unwrap().0.clone())));
+				},
+
+				//push input base
+				'I' => {
+					self.mstk.push(Obj::n(Float::with_val(self.wprec, self.envstk.last().unwrap().1.clone())));
+				},
+
+				//push output base
+				'O' => {
+					self.mstk.push(Obj::n(Float::with_val(self.wprec, self.envstk.last().unwrap().2.clone())));
+				},
+
+				//push working precision
+				'W' => {
+					self.mstk.push(Obj::n(Float::with_val(self.wprec, self.wprec)));
+				},
+
+				//create new k,i,o context
+				'{' => {
+					self.envstk.push((kdef(), idef(), odef()));
+				},
+
+				//revert to previous context
+				'}' => {
+					self.envstk.pop();
+					if self.envstk.is_empty() {
+						self.envstk.push((kdef(), idef(), odef()));	//ensure 1 entry always remains
+					}
+				},
+				/*--------------------------
+					REGISTERS AND MACROS
+				--------------------------*/
+				//save to top of register
+				's' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();					
+						if cmdstk.last().unwrap().is_empty()&&self.drs.is_none() {
+							self.report(DcError::NoRegister { cmd });
+						}
+						else {
+							let ri = if let Some(drs) = self.drs.take() {
+								drs
+							}
+							else {
+								cmdstk.last_mut().unwrap().pop().unwrap() as usize
+							};
+							if self.reg(ri).is_empty() {
+								self.regs.entry(ri).or_default().push(RegObj {
+									o: a,
+									a: Vec::new()
+								});
+							}
+							else {
+								self.regs.entry(ri).or_default().last_mut().unwrap().o = a;
+							}
+						}
+					}
+					else {
+						if self.drs.take().is_none() {	//invalidate DRS
+							cmdstk.last_mut().unwrap().pop();	//remove register name if not using DRS
+						}
+					}
+				},
+
+				//push to top of register
+				'S' => {
+					if self.check_n(cmd) {
+						let a=RegObj {
+							o: self.mstk.pop().unwrap(),
+							a: Vec::new()
+						};
+						if cmdstk.last().unwrap().is_empty()&&self.drs.is_none() {
+							self.report(DcError::NoRegister { cmd });
+						}
+						else {
+							let ri = if let Some(drs) = self.drs.take() {
+								drs
+							}
+							else {
+								cmdstk.last_mut().unwrap().pop().unwrap() as usize
+							};
+							self.regs.entry(ri).or_default().push(a);
+						}
+					}
+					else {
+						if self.drs.take().is_none() {	//invalidate DRS
+							cmdstk.last_mut().unwrap().pop();	//remove register name if not using DRS
+						}
+					}
+				},
+
+				//load from top of register
+				'l' => {
+					if cmdstk.last().unwrap().is_empty()&&self.drs.is_none() {
+						self.report(DcError::NoRegister { cmd });
+					}
+					else {
+						let ri = if let Some(drs) = self.drs.take() {
+							drs
+						}
+						else {
+							cmdstk.last_mut().unwrap().pop().unwrap() as usize
+						};
+						if self.reg(ri).is_empty() {
+							self.report(DcError::RegisterEmpty { cmd, reg: ri });
+						}
+						else {
+							self.mstk.push(self.reg(ri).last().unwrap().o.clone());
+						}
+					}
+				},
+
+				//pop from top of register
+				'L' => {
+					if cmdstk.last().unwrap().is_empty()&&self.drs.is_none() {
+						self.report(DcError::NoRegister { cmd });
+					}
+					else {
+						let ri = if let Some(drs) = self.drs.take() {
+							drs
+						}
+						else {
+							cmdstk.last_mut().unwrap().pop().unwrap() as usize
+						};
+						if self.reg(ri).is_empty() {
+							self.report(DcError::RegisterEmpty { cmd, reg: ri });
+						}
+						else {
+							self.mstk.push(self.regs.entry(ri).or_default().pop().unwrap().o);
+						}
+					}
+				},
+
+				//save to top-of-register's array
+				':' => {
+					if self.check_n(cmd) {
+						let b = self.mstk.pop().unwrap();
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a, &b]) {
+							if cmdstk.last().unwrap().is_empty()&&self.drs.is_none() {
+								self.report(DcError::NoRegister { cmd });
+							}
+							else {
+								let ri = if let Some(drs) = self.drs.take() {
+									drs
+								}
+								else {
+									cmdstk.last_mut().unwrap().pop().unwrap() as usize
+								};
+								if self.reg(ri).is_empty() {
+									self.regs.entry(ri).or_default().push(RegObj {
+										o: Obj::n(Float::with_val(self.wprec, 0)),	//create default register object if empty
+										a: Vec::new()
+									});
+								}
+								let int = b.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+								if let Some((rai, len)) = int.to_usize().and_then(|rai| Some((rai, rai.checked_add(1)?))) {	//index and minimum array length
+									if self.extend_arr(cmd, &[&a, &b], ri, len) {
+										self.regs.entry(ri).or_default().last_mut().unwrap().a[rai] = a;
+									}
+								}
+								else {
+									self.report(DcError::InvalidArgument { cmd, args: vec![a, b], reason: format!("Cannot possibly save to array index {}", int) });
+								}
+							}
+						}
+						else {
+							if self.drs.take().is_none() {	//invalidate DRS
+								cmdstk.last_mut().unwrap().pop();	//remove register name if not using DRS
+							}
+						}
+					}
+					else {
+						if self.drs.take().is_none() {	//invalidate DRS
+							cmdstk.last_mut().unwrap().pop();	//remove register name if not using DRS
+						}
+					}
+				},
+
+				//load from top-of-register's array
+				';' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							if cmdstk.last().unwrap().is_empty()&&self.drs.is_none() {
+								self.report(DcError::NoRegister { cmd });
+							}
+							else {
+								let ri = if let Some(drs) = self.drs.take() {
+									drs
+								}
+								else {
+									cmdstk.last_mut().unwrap().pop().unwrap() as usize
+								};
+								if self.reg(ri).is_empty() {
+									self.regs.entry(ri).or_default().push(RegObj {
+										o: Obj::n(Float::with_val(self.wprec, 0)),	//create default register object if empty
+										a: Vec::new()
+									});
+								}
+								let int = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+								if let Some((rai, len)) = int.to_usize().and_then(|rai| Some((rai, rai.checked_add(1)?))) {	//index and minimum array length
+									if self.extend_arr(cmd, &[&a], ri, len) {
+										self.mstk.push(self.reg(ri).last().unwrap().a[rai].clone());
+									}
+								}
+								else {
+									self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: format!("Cannot possibly load from array index {}", int) });
+								}
+							}
+						}
+						else {
+							if self.drs.take().is_none() {	//invalidate DRS
+								cmdstk.last_mut().unwrap().pop();	//remove register name if not using DRS
+							}
+						}
+					}
+					else {
+						if self.drs.take().is_none() {	//invalidate DRS
+							cmdstk.last_mut().unwrap().pop();	//remove register name if not using DRS
+						}
+					}
+				},
+
+				//load top-of-reg into buffer
+				'j' => {
+					if cmdstk.last().unwrap().is_empty()&&self.drs.is_none() {
+						self.report(DcError::NoRegister { cmd });
+					}
+					else {
+						let ri = if let Some(drs) = self.drs.take() {
+							drs
+						}
+						else {
+							cmdstk.last_mut().unwrap().pop().unwrap() as usize
+						};
+						if self.reg(ri).is_empty() {
+							self.report(DcError::RegisterEmpty { cmd, reg: ri });
+						}
+						else {
+							self.ro_buf = self.reg(ri).last().unwrap().clone();
+						}
+					}
+				},
+
+				//pop top-of-reg into buffer
+				'J' => {
+					if cmdstk.last().unwrap().is_empty()&&self.drs.is_none() {
+						self.report(DcError::NoRegister { cmd });
+					}
+					else {
+						let ri = if let Some(drs) = self.drs.take() {
+							drs
+						}
+						else {
+							cmdstk.last_mut().unwrap().pop().unwrap() as usize
+						};
+						if self.reg(ri).is_empty() {
+							self.report(DcError::RegisterEmpty { cmd, reg: ri });
+						}
+						else {
+							self.ro_buf = self.regs.entry(ri).or_default().pop().unwrap();
+						}
+					}
+				},
+
+				//save buffer to top-of-reg
+				'h' => {
+					if cmdstk.last().unwrap().is_empty()&&self.drs.is_none() {
+						self.report(DcError::NoRegister { cmd });
+					}
+					else {
+						let ri = if let Some(drs) = self.drs.take() {
+							drs
+						}
+						else {
+							cmdstk.last_mut().unwrap().pop().unwrap() as usize
+						};
+						self.regs.entry(ri).or_default().pop();
+						self.regs.entry(ri).or_default().push(self.ro_buf.clone());
+					}
+				},
+
+				//push buffer to register
+				'H' => {
+					if cmdstk.last().unwrap().is_empty()&&self.drs.is_none() {
+						self.report(DcError::NoRegister { cmd });
+					}
+					else {
+						let ri = if let Some(drs) = self.drs.take() {
+							drs
+						}
+						else {
+							cmdstk.last_mut().unwrap().pop().unwrap() as usize
+						};
+						self.regs.entry(ri).or_default().push(self.ro_buf.clone());
+					}
+				},
+
+				//push register depth
+				'Z' => {
+					if cmdstk.last().unwrap().is_empty()&&self.drs.is_none() {
+						self.report(DcError::NoRegister { cmd });
+					}
+					else {
+						let ri = if let Some(drs) = self.drs.take() {
+							drs
+						}
+						else {
+							cmdstk.last_mut().unwrap().pop().unwrap() as usize
+						};
+						self.mstk.push(Obj::n(Float::with_val(self.wprec, self.reg(ri).len())));
+					}
+				},
+
+				//specify manual register index
+				',' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							let int = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							if let Some(ri) = int.to_usize() {
+								self.drs = Some(ri);
+							}
+							else {
+								self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: format!("Register {} cannot possibly exist", int) });
+							}
+						}
+					}
+				},
+				/*------------
+					MACROS
+				------------*/
+
+				//convert least significant 32 bits to one-char string or first char of string to number
+				'a' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							if a.is_str() {
+								if a.str().is_empty() {
+									self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: "Cannot convert empty string to number".to_string() });
+								}
+								else {
+									self.mstk.push(Obj::n(Float::with_val(self.wprec, a.str().chars().next().unwrap() as u32)));
+								}
+							}
+							else {
+								if let Some(ia) = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0.to_u32() {
+									if let Some(res) = char::from_u32(ia) {
+										self.mstk.push(Obj::s(res.to_string()));
+									}
+									else {
+										self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: format!("Unable to convert number {} to character: not a valid Unicode value", ia) });
+									}
+								}
+								else {
+									self.report(DcError::InvalidArgument { cmd, args: vec![a.clone()], reason: format!("Unable to convert number {} to character: valid range is 0 to {}", a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0, u32::MAX) });
+								}
+							}
+						}
+					}
+				},
+
+				//convert number to UTF-8 string or back
+				'A' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							if a.is_str() {
+								self.mstk.push(Obj::n(Float::with_val(self.wprec, Integer::from_digits(a.str().as_bytes(), Order::Msf))));
+							}
+							else {
+								if let Ok(res) = String::from_utf8(a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0.to_digits::<u8>(Order::Msf)) {
+									self.mstk.push(Obj::s(res));
+								}
+								else {
+									self.report(DcError::InvalidArgument { cmd, args: vec![a.clone()], reason: format!("Unable to convert number {} to string: not valid UTF-8", a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0) });
+								}
+							}
+						}
+					}
+				},
+
+				//execute string as macro
+				'x' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if a.is_str() {
+							let frame = Frame::called(self.compile(a.into_str()), Source::Macro, cmd, cmdstk.last().unwrap(), pos);
+							backtrace::call(&mut cmdstk, frame);	//optimize tail call
+						}
+						else {
+							self.mstk.push(a);
+						}
+					}
+				},
+
+				//invert next conditional
+				'!' => {
+					inv = !inv;
+				},
+
+				//conditionally execute macro
+				'<'|'='|'>' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();	//deliberately reverse order
+						let b = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a, &b]) {
+							let mut mac = String::new();
+							if cmdstk.last().unwrap().is_empty()&&self.drs.is_none() {
+								self.report(DcError::NoRegister { cmd });
+							}
+							else {
+								let ri = if let Some(drs) = self.drs.take() {
+									drs
+								}
+								else {
+									cmdstk.last_mut().unwrap().pop().unwrap() as usize
+								};
+								if self.reg(ri).is_empty() {
+									self.report(DcError::RegisterEmpty { cmd, reg: ri });
+								}
+								else {
+									mac = self.reg(ri).last().unwrap().o.as_str().unwrap_or_default().to_string();	//get macro if possible, numbers are ignored
+								}
+							}
+							if !mac.is_empty() && inv != match cmd {	//like xor
+								'<' => { a.num() < b.num() },
+								'=' => { a.num() == b.num() },
+								'>' => { a.num() > b.num() },
+								_ => {false},
+							}
+							{
+								let frame = Frame::called(self.compile(mac), Source::Macro, cmd, cmdstk.last().unwrap(), pos);
+								backtrace::call(&mut cmdstk, frame);	//optimize tail call
+							}
+						}
+						else {
+							if self.drs.take().is_none() {	//invalidate DRS
+								cmdstk.last_mut().unwrap().pop();	//remove register name if not using DRS
+							}
+						}
+					}
+					else {
+						if self.drs.take().is_none() {	//invalidate DRS
+							cmdstk.last_mut().unwrap().pop();	//remove register name if not using DRS
+						}
+					}
+					inv = false;	//always reset inversion
+				},
+
+				//auto-macro
+				'X' => {
+					if self.check_n(cmd) {
+						let b = self.mstk.pop().unwrap();
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a, &b]) {
+							let int = b.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							if let Some(reps) = int.to_usize() {
+								if reps>0 && !a.str().is_empty() {
+									let frame = Frame::called(self.compile(a.into_str()), Source::Macro, cmd, cmdstk.last().unwrap(), pos).times(reps);	//one frame counts the repetitions
+									backtrace::call(&mut cmdstk, frame);	//optimize tail call
+								}
+							}
+							else {
+								self.report(DcError::InvalidArgument { cmd, args: vec![a, b], reason: format!("Invalid macro repeat count: {}", int) });
+							}
+						}
+					}
+				},
+
+				//quit dcim
+				'q' => {
+					return Ok(Some(self.drs.map_or(0, |drs| drs as i32)));	//DRS is used as the exit code
+				},
+
+				//quit a macro calls
+				'Q' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							let int = a.num().to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
+							if let Some(mut num) = int.to_usize() {
+								while num>0 && !cmdstk.is_empty() {
+									num = cmdstk.last_mut().unwrap().quit(num);	//frames may stand for several invocations because of tail calls
+									if cmdstk.last().unwrap().is_done() {
+										cmdstk.pop();
+									}
+								}
+								if cmdstk.is_empty() {
+									cmdstk.push(Frame::new(Rc::new(Code::new("".into())), Source::Input));	//guarantee at least one object
+								}
+							}
+							else {
+								self.report(DcError::InvalidArgument { cmd, args: vec![a], reason: format!("Cannot possibly quit {} levels", int) });
+							}
+						}
+					}
+				},
+
+				//prompt and execute
+				'?' => {
+					let mut prompt_in = String::new();
+					if let Err(error) = stdin().read_line(&mut prompt_in) {
+						self.report(DcError::System { cmd, reason: format!("Unable to read input: {}", error) });
+					}
+					else {
+						prompt_in = prompt_in.trim_end_matches(char::is_whitespace).to_string();	//trim trailing LF
+						let frame = Frame::called(Rc::new(Code::new(prompt_in.into())), Source::Input, cmd, cmdstk.last().unwrap(), pos);
+						backtrace::call(&mut cmdstk, frame);	//optimize tail call
+					}
+				},
+
+				//execute file as script, 'M' only if it hasn't been executed before
+				'&'|'M' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							let path = self.find_file(a.str());
+							let id = path.canonicalize().unwrap_or(path.clone());	//identify files regardless of how they're named
+							match self.sandbox.check_file(&path) {
+								Some(_) if cmd=='M' && self.included.contains(&id) => {},	//already included
+								Some(path) => match std::fs::read_to_string(path) {
+									Ok(script) => {
+										self.included.insert(id);
+										let frame = Frame::called(self.compile(strip_comments(&script)), Source::Named(a.str().to_string()), cmd, cmdstk.last().unwrap(), pos);
+										cmdstk.push(frame);
+									},
+									Err(error) => {
+										self.report(DcError::System { cmd, reason: format!("Unable to read file \"{}\": {}", a.str(), error) });
+									},
+								},
+								None => {
+									self.report(DcError::Forbidden { cmd, what: format!("Reading file \"{}\"", a.str()) });
+								},
+							}
+						}
+					}
+				},
+
+				//save state to file
+				'b' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							match self.sandbox.check_file(a.str()).map(|path| std::fs::write(path, self.save_state())) {
+								Some(Ok(())) => {},
+								Some(Err(error)) => {
+									self.report(DcError::System { cmd, reason: format!("Unable to write file \"{}\": {}", a.str(), error) });
+								},
+								None => {
+									self.report(DcError::Forbidden { cmd, what: format!("Writing file \"{}\"", a.str()) });
+								},
+							}
+						}
+					}
+				},
+
+				//load state from file
+				'B' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a]) {
+							match self.sandbox.check_file(a.str()).map(std::fs::read_to_string) {
+								Some(Ok(state)) => {
+									if let Err(e) = self.load_state(&state) {
+										self.report(e);
+									}
+								},
+								Some(Err(error)) => {
+									self.report(DcError::System { cmd, reason: format!("Unable to read file \"{}\": {}", a.str(), error) });
+								},
+								None => {
+									self.report(DcError::Forbidden { cmd, what: format!("Reading file \"{}\"", a.str()) });
+								},
+							}
+						}
+					}
+				},
+
+				//export registers (or everything if none are selected) as script
+				'E' => {
+					if self.check_n(cmd) {
+						let b = self.mstk.pop().unwrap();
+						let a = self.mstk.pop().unwrap();
+						if self.check_t(cmd, &[&a, &b]) {
+							let mut sel: Vec<usize> = Vec::new();
+							for c in a.str().chars() {
+								if !sel.contains(&(c as usize)) {sel.push(c as usize);}
+							}
+							let script = self.export_script(if sel.is_empty() {None} else {Some(&sel)});
+							match self.sandbox.check_file(b.str()).map(|path| std::fs::write(path, script)) {
+								Some(Ok(())) => {},
+								Some(Err(error)) => {
+									self.report(DcError::System { cmd, reason: format!("Unable to write file \"{}\": {}", b.str(), error) });
+								},
+								None => {
+									self.report(DcError::Forbidden { cmd, what: format!("Writing file \"{}\"", b.str()) });
+								},
+							}
+						}
+					}
+				},
+
+				//get environment variable
+				'$' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if !self.sandbox.env_vars {
+							self.report(DcError::Forbidden { cmd, what: "Reading environment variables".into() });
+						}
+						else if self.check_t(cmd, &[&a]) {
+							match std::env::var(a.str()) {
+								Ok(val) => {
+									self.mstk.push(Obj::s(val));
+								},
+								Err(err) => {
+									self.report(DcError::System { cmd, reason: format!("Unable to get value of \"{}\": {}", a.str(), err) });
+								},
+							}
+						}
+					}
+				},
+
+				//execute os command(s)
+				'\\' => {
+					if self.check_n(cmd) {
+						let a = self.mstk.pop().unwrap();
+						if !self.sandbox.os_commands {
+							self.report(DcError::Forbidden { cmd, what: "Executing OS commands".into() });
+						}
+						else if self.check_t(cmd, &[&a]) {
+							for oscmd in a.str().split(';') {
+								if let Some((var, val)) = oscmd.split_once('=') {	//set variable
+									std::env::set_var(var, val);
+								}
+								else {	//normal command
+									let mut args: Vec<&str> = oscmd.trim().split(' ').collect();
+									match std::process::Command::new(args.remove(0)).args(args).spawn() {
+										Ok(mut child) => {
+											if let Ok(stat) = child.wait() {
+												if let Some(code) = stat.code() {
+													if code!=0 {self.report(DcError::System { cmd, reason: format!("OS command \"{}\" exited with code {}", oscmd, code) });}
+												}
+											}
+										},
+										Err(error) => {
+											self.report(DcError::System { cmd, reason: format!("Unable to execute OS command \"{}\": {}", oscmd, error) });
+										},
+									}
+								}
+							}
+						}
+					}
+				},
+
+				//skip #comment until end of line
+				'#' => {
+					cmdstk.last_mut().unwrap().skip_past('\n');
+				},
+
+				//notify on invalid command, keep going
+				_ => {
+					if !cmd.is_whitespace()&&cmd!='\0' { self.report(DcError::InvalidCommand { cmd }); }
+				},
+			}
+			if let Some(max) = self.limits.stack_depth {
+				if self.mstk.len()>max {
+					self.mstk.truncate(max);	//remove excess objects
+					self.stop(cmd, LimitKind::StackDepth(max));
+				}
+			}
+			self.flush(&cmdstk, depth, pos);
+			if let Some(e) = self.abort.take() {
+				return Err(e);	//strict mode or exceeded limit, stop at first error
+			}
+			while let Some(ptr) = cmdstk.last_mut() {
+				if !ptr.is_empty() {break;}
+				if !ptr.restart() {	//next repetition of 'X' or done
+					cmdstk.pop();
+				}
+			}
+		}
+
+		Ok(None)
+	}
+}
+
+//scientific notation to Float, for brevity
+fn sci_to_flt(prec: u32, man: i128, exp: i128) -> Float {
+	Float::with_val(prec, man)*Float::with_val(prec, exp).exp10()
+}
+
+//slightly more efficient string reverser, at least on my machine
+fn rev_str(mut instr: String) -> String {
+	let mut outstr = String::new();
+	while !instr.is_empty() {
+		outstr.push(instr.pop().unwrap());
+	}
+	outstr
+}
+
+//custom number printing function
+//if output base is over 36, prints in custom "any-base" notation
+//otherwise, applies precision like dc and converts from exponential notation if not too small
+//upper bound for the length of flt_to_str(num, obase, oprec) and the strings it creates on the way, without creating them
+fn flt_str_len(num: &Float, obase: &Integer, oprec: &Integer) -> usize {
+	let Some(exp) = num.get_exp() else {
+		return 12;	//zero, infinity or NaN
+	};
+	let bits = obase.to_f64().log2();	//bits per digit
+	let digits_for = |b: u64| (b as f64/bits).ceil() as u64 + 1;	//digits needed for b bits, with room for rounding
+	let frac_bits = (i64::from(num.prec()) - i64::from(exp)).max(0) as u64;	//bits after the point
+	let int = digits_for(exp.max(0) as u64);	//digits of the integer part
+	let oprec = if *oprec<0 {None} else {Some(oprec.to_u64().unwrap_or(u64::MAX))};
+	let digits = if *obase>36 {
+		let frac = digits_for(frac_bits);	//every scale-up moves this many bits to the integer part
+		int.saturating_add(oprec.map_or(frac, |k| k.min(frac)))
+	}
+	else {
+		match oprec {
+			None => digits_for(u64::from(num.prec())),
+			Some(k) => int.saturating_add(if obase.is_even() {k.min(frac_bits)} else {k}),
+		}
+	};
+	let width = if *obase>36 {obase.to_string().len() as u64 + 1} else {1};	//any-base digits are separated by spaces
+	digits.saturating_mul(width).saturating_add(16).try_into().unwrap_or(usize::MAX)	//sign, separator, exponent and parentheses
+}
+
+fn flt_to_str(mut num: Float, obase: Integer, oprec: Integer) -> String {
+	if num.is_zero() {
+		return String::from(if obase>36 {"(0)"} else {"0"});	//causes issues, always "0" regardless of parameters
+	}
+	if num.is_infinite() {
+		return String::from("Infinity");
+	}
+	if num.is_nan() {
+		return String::from("Not a number");
+	}
+
+	if obase>36 {	//any-base printing (original base conversion algorithm out of necessity, limited precision possible)
+		let mut outstr = String::from(if num<0 {"(-"} else {"("});	//apply negative sign
+		num = num.abs();
+		let mut scale: usize = 0;	//amount to shift fractional separator in output
+		while !num.is_integer()&&(oprec<0||scale<oprec) {	//turn into integer scaled by power of obase, apply output precision if enabled
+			let temp = num.clone() * &obase;	//preview scale-up
+			if temp.is_infinite() {	//possible with high precision due to Float's exponent limitation
+				num /= &obase;	//prevent overflow in later "extra precision" part
+				break;	//disregard further precision
+			}
+			num = temp;	//if ok, commit to scale-up
+			scale +=1;
+		}
+		num *= &obase;	//get extra precision for last digit
+		let mut int = num.to_integer().unwrap();	//convert to Integer
+		int /= &obase;	//undo extra precision
+		let mut dig = Integer::from(1);	//current digit value
+		while dig<=int {
+			dig *= &obase;	//get highest required digit value
+		}
+		dig /= &obase;	//correct off-by-one error
+		loop {	//separate into digits
+			let (quot, rem) = int.clone().div_rem_euc(dig.clone());	//separate into amount of current digit and remainder
+			outstr.push_str(&quot.to_string());	//print amount of current digit
+			outstr.push(' ');
+			int = rem;	//switch to remainder
+			if dig==1 {break;}	//stop when all digits done
+			dig /= &obase;	//switch to next lower digit
+		}
+		if scale>0 {
+			if let Some((idx, _)) = outstr.rmatch_indices(' ').nth(scale) {	//find location for fractional separator
+				unsafe { outstr.as_bytes_mut()[idx] = b'.'; }	//and insert it
+			}
+			else {
+				outstr.insert_str(if outstr.starts_with("(-") {2} else {1}, "0.");	//number has no integer part, add "0."
+			}
+		}
+		outstr.pop();
+		outstr.push(')');
+		outstr
+	}
+	else {	//normal printing
+		let radix = obase.to_i32().unwrap_or(10);	//always fits, obase is at most 36 here
+		let oprec = if radix%2==0 && oprec>=0 {	//in even bases, binary fractions end after at most as many digits as there are bits after the point
+			oprec.min(Integer::from((i64::from(num.prec()) - i64::from(num.get_exp().unwrap())).max(0)))
+		}
+		else {
+			oprec
+		};
+		let mut outstr = num.to_string_radix(
+			radix,
+			if oprec<0 {
+				None
+			}
+			else {
+				(oprec + Integer::from(
+					num.to_integer_round(Round::Zero).unwrap().0	//integer part of num
+					.to_string_radix(radix)	//...to string
+					.trim_start_matches('-').len())).to_usize() 	//...length without negative sign, print exactly if too large
+			}
+		);
+		if obase <= 10 {	//unify exponent symbol without searching the whole string
+			let im = outstr.len()-1;	//max index
+			unsafe {
+				let bytes = outstr.as_bytes_mut();
+				for ir in 0..=im {	//right offset
+					if ir>10 {break;}	//exponents cannot have more digits, longest is @-323228496
+					if bytes[im-ir]==b'e' {
+						bytes[im-ir] = b'@';	//replace
+						break;
+					}
+				}
+			}
+		}
+		if outstr.starts_with('-') {
+			outstr = outstr.replacen('-', "_", 1);	//replace negative sign
+		}
+		if outstr[if outstr.len()>11 {outstr.len()-11} else {0}..].contains('@') {	//efficiently check if in exponential notation
+			let (mut mpart, epart) = outstr.rsplit_once('@').unwrap();
+			mpart = mpart.trim_end_matches('0').trim_end_matches('.');	//remove trailing zeros from mantissa
+			let eint = epart.parse::<i32>().unwrap();	//isolate exponential part
+			if eint<0 && eint>-10 {
+				outstr = "0.".to_string() + &"0".repeat(eint.unsigned_abs() as usize -1) + &mpart.replacen('.', "", 1);	//convert from exponential notation if not too small
+				if num<0 {
+					let (ipart, fpart) = outstr.split_once('_').unwrap();
+					outstr = "_".to_string() + ipart + fpart;	//move negative sign to front
+				}
+			}
+			else {
+				outstr = mpart.to_string() + "@" + &epart.replacen('-', "_", 1);	//reassemble, replace negative sign in exponent
+			}
+		}
+		else {	//if in normal notation
+			if let Some((ipart, fpart)) = outstr.split_once('.') {
+				outstr = ipart.to_string() + "." + fpart.trim_end_matches('0');	//trim trailing zeros
+			}
+		}
+		outstr.trim_end_matches('.').to_string()	//remove fractional separator
+	}
+}
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+/// Upper bounds on the resources used by one call of [`Interpreter::exec`](crate::Interpreter::exec).
+///
+/// `None` means unlimited, which is the default. Exceeding a limit always stops execution with [`DcError::LimitExceeded`](crate::DcError::LimitExceeded), strict mode or not.
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+	/// Amount of commands executed.
+	pub commands: Option<u64>,
+	/// Elapsed wall-clock time.
+	pub time: Option<Duration>,
+	/// Amount of objects on the main stack.
+	pub stack_depth: Option<usize>,
+	/// Length of register arrays.
+	pub array_length: Option<usize>,
+	/// Length of strings created by `+` and `*` or by converting numbers for printing and `"`, in bytes. Also limits the output precision set by `k`.
+	pub string_length: Option<usize>,
+	/// Working precision set by `w`, in bits.
+	pub wprec: Option<u32>,
+}
+
+impl Limits {
+	/// No limits at all.
+	pub fn unlimited() -> Self {
+		Self::default()
+	}
+}
@@ -1,7 +1,13 @@
-use rug::{Integer, integer::Order, Complete, Float, float::{Round, Constant}, ops::Pow, rand::RandState};
-use std::io::{stdin, stdout, Write};
-use std::time::{SystemTime, Duration};
-use std::cmp::Ordering;
+use dcim::{Interpreter, DcError, Obj, Location, Source, Snapshot, DebugHook, CallStack, Sandbox, FileAccess, Limits, strip_comments, open_string, is_unfinished, CONSTANT_NAMES};
+use rustyline::{Editor, Helper, Context, error::ReadlineError, history::FileHistory};
+use rustyline::{completion::{Completer, Pair}, hint::Hinter, highlight::Highlighter, validate::Validator};
+use std::io::{stdin, stdout, IsTerminal};
+use std::panic::AssertUnwindSafe;
+use std::sync::{OnceLock, atomic::{AtomicBool, Ordering}};
+
+static SAVE_FILE: OnceLock<String> = OnceLock::new();	//where to save the state on exit
+static UNDO_LEVELS: OnceLock<usize> = OnceLock::new();	//how many input lines can be undone in interactive mode
+static EXECUTING: AtomicBool = AtomicBool::new(false);	//Ctrl-C interrupts execution instead of exiting
 
 const HELPMSG: &str = "
 ╭─────────────────────────╮
@@ -17,2087 +23,742 @@ Most basic GNU dc features are unaltered, full documentation at https://github.c
 
 Options and syntax:
 
-<nothing> | --interactive | -i | i
-	Interactive mode, standard prompt loop.
+dcim [--strict] [--sandbox] [--allow cap ...] [--limit name=value ...] [--load file] [--save file] [--no-init] [mode]
 
-(--expression | -e | e) expr1 expr2 expr3 ... [?]
-	Expression mode, executes expressions in order. If the last argument is '?', enters interactive mode after expressions are done.
+--strict
+	Stop at the first error instead of continuing, exit with that error's code.
 
-(--file | -f | f) file1 file2 file3 ... [?]
-	File mode, executes contents of files in order. '?' behaves the same as with -e.
+--sandbox
+	For untrusted code: disables OS commands (\\), environment variables ($), file access (&, M, b, B, E) and the process-killing constants abort/crash/panic.
 
---help | -h | h
-	Print this help message.
-";
+--allow os|env|exit|files|files=DIR
+	Re-enables one capability in sandbox mode (implies --sandbox), can be repeated. files=DIR only allows files inside of DIR.
 
-//environment parameter defaults and storage, initialized in main
-fn kdef() -> Integer { Integer::from(-1) }
-fn idef() -> Integer { Integer::from(10) }
-fn odef() -> Integer { Integer::from(10) }
-static mut ENVSTK: Vec<(Integer, Integer, Integer)> = Vec::new();	//stores (k,i,o) tuples, used by '{' and '}'
-static mut WPREC: u32 = 256;	//working precision (rug Float mantissa length)
+--limit commands|time|stack|array|string|wprec=value
+	Stops every expression, file or line that exceeds this limit, can be repeated. Limits are the amount of commands executed,
+	time in seconds, main stack depth, register array length, string length in bytes and working precision.
 
-//basic object on a dc stack, need to differentiate between numbers and strings
-#[derive(Clone)]
-struct Obj {
-	t: bool,	//type, true iff string
-	n: Float,	//number
-	s: String,	//string
-}
+--load file
+	Restore the state (stack, registers, parameters) saved with --save or the b command before executing anything.
 
-//register object, may have a dynamic array
-#[derive(Clone)]
-struct RegObj {
-	o: Obj,			//principal object
-	a: Vec<Obj>,	//associated array
-}
+--save file
+	Save the state when dcim exits, for use with --load or the B command.
 
-//shorthand constructors
-impl Obj {
-	fn n(number: Float) -> Self {	//number variant
-		Obj {
-			t: false,
-			n: number,
-			s: String::new()
-		}
-	}
-	fn s(string: String) -> Self {	//string variant
-		Obj {
-			t: true,
-			n: flt_def(),
-			s: string
-		}
-	}
-}
+--trace
+	Print every executed command with its macro nesting depth and the top of the stack to standard error.
+
+--debug
+	Pause before the first command and show the debugger prompt on standard error, enter h there for a list of debugger commands.
+
+--undo levels
+	Amount of input lines that can be undone in interactive mode, 10 by default.
+
+--no-init
+	Don't execute the init file before entering the mode. The init file is $DCIM_INIT if set, $XDG_CONFIG_HOME/dcim/init.dc or ~/.config/dcim/init.dc otherwise.
 
-static mut MSTK: Vec<Obj> = Vec::new();	//main stack
+<nothing> | --interactive | -i | i
+	Interactive mode, standard prompt loop. The prompt is only shown if standard input and output are terminals.
+	Unfinished strings and any-base numbers are continued on the next line (with prompt \". \"), Ctrl-C discards them.
+	On a terminal, lines can be edited and the history (kept in $XDG_CONFIG_HOME/dcim/history or ~/.config/dcim/history) is searched with Ctrl-R.
+	Tab completes constant and unit names inside strings that are looked up with \".
+
+(--expression | -e | e) expr1 expr2 expr3 ... [?] [-- arg1 arg2 ...]
+	Expression mode, executes expressions in order. If the last argument is '?', enters interactive mode after expressions are done.
+
+(--file | -f | f) file1 file2 file3 ... [?] [-- arg1 arg2 ...]
+	File mode, executes contents of files in order, '-' reads a script from standard input. '?' behaves the same as with -e.
 
-const REG_DEF: Vec<RegObj> = Vec::new();
-static mut REGS: [Vec<RegObj>; 65536] = [REG_DEF; 65536];	//array of registers
-static mut RO_BUF: Vec<RegObj> = Vec::new();	//vec because constant constructors are impossible, initialized in main
+In interactive mode, a string in register > replaces the prompt \"> \". It may contain placeholders for the stack depth (%z),
+input base (%i), output base (%o), output precision (%k), working precision (%w) and % itself (%%), like [%z %k> ]s>.
+The lines :undo and :redo undo and redo the changes of input lines to the stack, registers and parameters.
+A number N in register < prints the top N stack objects (like f) after every input line, like 3s<.
 
-static mut DRS: usize = 0;	//direct register selector
-static mut DRS_EN: bool = false;	//DRS valid?
+Arguments after -- are available to the script in register $: l$ is the amount of arguments, 0;$ the first one (as a string).
 
-static mut RNG: Vec<RandState> = Vec::new();	//same problem as with RO_BUF
+(--stream | -s | s) [-n] macro
+	Stream mode, pushes every line of standard input as a string (or as a number with -n) and executes the macro on it.
+	For example, dcim -s -n 'd*p' prints the square of every input number.
 
-const INT_ORD_DEF: (Integer, Ordering) = (Integer::ZERO, Ordering::Equal);	//default tuple for to_integer_round().unwrap_or()
-fn flt_def() -> Float {Float::new(1)}	//default Float value for unused Obj.n
+--help | -h | h
+	Print this help message.
+
+Environment variables:
+	DCIM_INIT	init file to use instead of init.dc in $XDG_CONFIG_HOME/dcim or ~/.config/dcim
+	DCIM_PATH	directories (separated like PATH) searched by & and M for relative file names
+
+Exit codes (expression and file mode):
+	0	success, or the DRS value when quitting with q
+	1	at least one error occurred (also when quitting with q without DRS), invalid option or unreadable file
+	2-11	(only with --strict) execution stopped by an error:
+		2 insufficient arguments, 3 invalid types, 4 arithmetic, 5 invalid argument,
+		6 register, 7 parsing, 8 unknown constant, 9 invalid command, 10 OS/file access,
+		11 forbidden by sandbox
+	12	resource limit exceeded (even without --strict)
+	130	interrupted with Ctrl-C (in interactive mode, Ctrl-C only stops the running command line)
+";
 
 fn main() {
 	let mut args: Vec<String> = std::env::args().collect();
 	args.remove(0);	//remove name of executable
 
-	//init everything that doesn't have a const constructor
-	unsafe {
-		ENVSTK.push((kdef(), idef(), odef()));	//initialize env params
-		RO_BUF.push(RegObj{	//and RegObj buffer
-			a: Vec::new(),
-			o: Obj::n(Float::with_val(WPREC, 0))
-		});
-		//initialize RNG with system time (* PID for a bit less predictability)
-		RNG.push(RandState::new());
-		RNG[0].seed(&(Integer::from(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or(Duration::MAX).as_nanos()) * std::process::id()));
+	let mut dc = Interpreter::new();
+	let mut sandbox: Option<Sandbox> = None;
+	let mut limits = Limits::unlimited();
+	let mut init = true;
+	let mut trace = false;
+	let mut debug = false;
+
+	//global options before the mode
+	while let Some(opt) = args.first() {
+		match opt.as_str() {
+			"--strict" => {
+				dc.set_strict(true);
+			},
+			"--sandbox" => {
+				sandbox.get_or_insert_with(Sandbox::locked);
+			},
+			"--allow" => {
+				args.remove(0);
+				let sb = sandbox.get_or_insert_with(Sandbox::locked);
+				match args.first().map(|cap| cap.as_str()) {
+					Some("os") => {sb.os_commands = true;},
+					Some("env") => {sb.env_vars = true;},
+					Some("exit") => {sb.process_exit = true;},
+					Some("files") => {sb.files = FileAccess::All;},
+					Some(cap) if cap.starts_with("files=") => {
+						let dir = cap.trim_start_matches("files=").into();
+						match &mut sb.files {
+							FileAccess::Within(dirs) => {dirs.push(dir);},
+							FileAccess::Nothing => {sb.files = FileAccess::Within(vec![dir]);},
+							FileAccess::All => {},
+						}
+					},
+					Some(cap) => {
+						eprintln!("! Invalid capability \"{}\", use h for option syntax help", cap);
+						std::process::exit(1);
+					},
+					None => {
+						eprintln!("! No capability provided for --allow");
+						std::process::exit(1);
+					},
+				}
+			},
+			"--load" => {
+				args.remove(0);
+				let file = args.first().cloned().unwrap_or_default();
+				let res = match std::fs::read_to_string(&file) {
+					Ok(state) => dc.load_state(&state).map_err(|e| e.to_string()),
+					Err(error) => Err(error.to_string()),
+				};
+				if let Err(reason) = res {
+					eprintln!("! Unable to load state from \"{}\": {}", file, reason);
+					std::process::exit(1);
+				}
+			},
+			"--save" => {
+				args.remove(0);
+				if let Some(file) = args.first() {
+					let _ = SAVE_FILE.set(file.clone());
+				}
+				else {
+					eprintln!("! No file name provided for --save");
+					std::process::exit(1);
+				}
+			},
+			"--no-init" => {
+				init = false;
+			},
+			"--trace" => {
+				trace = true;
+			},
+			"--debug" => {
+				debug = true;
+			},
+			"--undo" => {
+				args.remove(0);
+				match args.first().and_then(|n| n.parse::<usize>().ok()) {
+					Some(levels) => {
+						let _ = UNDO_LEVELS.set(levels);
+					},
+					None => {
+						eprintln!("! Invalid amount of undo levels, use h for option syntax help");
+						std::process::exit(1);
+					},
+				}
+			},
+			"--limit" => {
+				args.remove(0);
+				let limit = args.first().map(|l| l.as_str()).unwrap_or("");
+				if set_limit(&mut limits, limit).is_none() {
+					eprintln!("! Invalid limit \"{}\", use h for option syntax help", limit);
+					std::process::exit(1);
+				}
+			},
+			_ => {break;},
+		}
+		args.remove(0);
+	}
+	if let Some(sb) = sandbox {
+		dc.set_sandbox(sb);
+	}
+	dc.set_limits(limits);
+	if let Some(dirs) = std::env::var_os("DCIM_PATH") {
+		dc.set_search_path(std::env::split_paths(&dirs).collect());
+	}
+	if trace || debug {
+		dc.set_debug_hook(Some(debug_hook(trace, debug, &dc)));
+	}
+	let interrupt = dc.interrupt_flag();
+	if let Err(error) = ctrlc::set_handler(move || {
+		if EXECUTING.load(Ordering::Relaxed) {
+			interrupt.store(true, Ordering::Relaxed);
+		}
+		else {
+			std::process::exit(130);
+		}
+	}) {
+		eprintln!("! Unable to handle Ctrl-C: {}", error);
+	}
+	if init && !matches!(args.first().map(|m| m.as_str()), Some("--help"|"-h"|"h")) {
+		init_file(&mut dc);
+	}
+
+	//script arguments
+	if let Some(idx) = args.iter().position(|arg| arg=="--") {
+		let script_args = args.split_off(idx+1);
+		args.pop();	//remove --
+		dc.set_args(&script_args);
 	}
 
-	if args.is_empty() {
-		interactive_mode();	//default to interactive
+	let ok = if args.is_empty() {
+		interactive_mode(&mut dc);	//default to interactive
+		true
 	}
 	else {
 		let mode = args.remove(0);
 		match mode.as_str() {
 			"--interactive"|"-i"|"i" => {
 				//ability to force interactive mode just in case
-				interactive_mode();
+				interactive_mode(&mut dc);
+				true
 			},
 			"--expression"|"-e"|"e" => {
-				expression_mode(args);
+				expression_mode(&mut dc, args);
+				dc.error_count()==0
 			},
 			"--file"|"-f"|"f" => {
-				file_mode(args);
+				file_mode(&mut dc, args) && dc.error_count()==0
+			},
+			"--stream"|"-s"|"s" => {
+				stream_mode(&mut dc, args) && dc.error_count()==0
 			},
 			"--help"|"-h"|"h" => {
 				println!("{}", HELPMSG);
+				true
 			},
 			_ => {
 				eprintln!("! Invalid option \"{}\", use h for option syntax help", mode);
+				false
 			},
 		}
-	}
+	};
+	quit(&dc, if ok {0} else {1});
 }
 
-//interactive/shell mode, the default
-fn interactive_mode() {
-	//prompt loop
-	loop {
-		//prompt for user input
-		print!("> ");
-		stdout().flush().unwrap();
-		let mut input = String::new();
-		match stdin().read_line(&mut input) {
-			Ok(_) => {},
-			Err(error) => {
-				eprintln!("! Unable to read standard input: {}", error);
-				break;
-			}
+//saves the state if requested, then exits
+fn quit(dc: &Interpreter, code: i32) -> ! {
+	if let Some(file) = SAVE_FILE.get() {
+		if let Err(error) = std::fs::write(file, dc.save_state()) {
+			eprintln!("! Unable to save state to \"{}\": {}", file, error);
+			std::process::exit(if code==0 {1} else {code});
 		}
-		if input.is_empty() {
-			print!("\r");
-			std::process::exit(0);	//stop on end of pipe input
-		}
-		input = input.trim_end_matches('\n').to_string();	//remove trailing LF
+	}
+	std::process::exit(code);
+}
 
-		unsafe {
-			exec(input);
-		}
+//parses name=value for --limit, None if invalid
+fn set_limit(limits: &mut Limits, limit: &str) -> Option<()> {
+	let (name, val) = limit.split_once('=')?;
+	match name {
+		"commands" => {limits.commands = Some(val.parse().ok()?);},
+		"time" => {limits.time = Some(std::time::Duration::try_from_secs_f64(val.parse().ok()?).ok()?);},
+		"stack" => {limits.stack_depth = Some(val.parse().ok()?);},
+		"array" => {limits.array_length = Some(val.parse().ok()?);},
+		"string" => {limits.string_length = Some(val.parse().ok()?);},
+		"wprec" => {limits.wprec = Some(val.parse().ok()?);},
+		_ => {return None;},
 	}
+	Some(())
 }
 
-fn expression_mode(exprs: Vec<String>) {
-	if !exprs.is_empty() {
-		for i in 0..exprs.len() {
-			if i==exprs.len()-1&&exprs[i]=="?" {
-				interactive_mode();	//if last expression is "?", enter prompt loop
-			}
-			else {
-				unsafe {
-					exec(exprs[i].clone());
-				}
-			}
-		}
+//executes input (from the file with this name) while Ctrl-C interrupts it
+fn exec(dc: &mut Interpreter, input: &str, name: Option<&str>) -> Result<Option<i32>, DcError> {
+	EXECUTING.store(true, Ordering::Relaxed);
+	let res = match name {
+		Some(name) => dc.exec_named(input, name),
+		None => dc.exec(input),
+	};
+	EXECUTING.store(false, Ordering::Relaxed);
+	res
+}
+
+//executes input, quits if requested by q or an error in strict mode
+fn run(dc: &mut Interpreter, input: &str, name: Option<&str>) {
+	match exec(dc, input, name) {
+		Ok(None) => {},
+		Ok(Some(code)) => {
+			//without an exit code from the DRS, q still reports earlier errors
+			quit(dc, if dc.drs().is_none() && dc.error_count()>0 {1} else {code});
+		},
+		Err(e) => {
+			quit(dc, e.exit_code());
+		},
 	}
 }
 
-fn file_mode(files: Vec<String>) {
-	if files.is_empty() {
-		eprintln!("! No file name provided");
+//interactive/shell mode, the default
+fn interactive_mode(dc: &mut Interpreter) {
+	//panics only abort the current line
+	std::panic::set_hook(Box::new(|info| {
+		let msg = info.payload().downcast_ref::<String>().map(|s| s.as_str())
+			.or_else(|| info.payload().downcast_ref::<&str>().copied())
+			.unwrap_or("unknown cause");
+		eprintln!("! Internal error: {}, stack and registers are kept", msg);
+	}));
+	//line editor on terminals, plain lines without prompt otherwise to not pollute piped output
+	let mut editor = if stdin().is_terminal() && stdout().is_terminal() {
+		match Editor::<DcHelper, FileHistory>::new() {
+			Ok(mut ed) => {
+				ed.set_helper(Some(DcHelper));
+				if let Some(file) = history_file() {
+					let _ = ed.load_history(&file);	//no history yet
+				}
+				Some(ed)
+			},
+			Err(error) => {
+				eprintln!("! Unable to start line editor: {}", error);
+				None
+			},
+		}
 	}
 	else {
-		for i in 0..files.len() {
-			if i==files.len()-1&&files[i]=="?"{
-				interactive_mode();	//if last filename is "?", enter prompt loop
-			}
-			else {
-				match std::fs::read_to_string(files[i].clone()) {
-					Ok(script) => {
-						let mut script_nc = String::new();	//script with comments removed
-						for line in script.split('\n') {
-							script_nc.push_str(line.split_once('#').unwrap_or((line,"")).0);	//remove comment on every line
-							script_nc.push('\n');
-						}
-						unsafe {
-							exec(script_nc);
-						}
-								
+		None
+	};
+	//states before previous input lines, most recent last
+	let levels = UNDO_LEVELS.get().copied().unwrap_or(10);
+	let mut undo: Vec<Snapshot> = Vec::new();
+	let mut redo: Vec<Snapshot> = Vec::new();
+	//state after the last change, only copied again after lines that may have changed it
+	let mut current = if levels>0 {Some(dc.snapshot())} else {None};
+	let mut generation = dc.generation();
+	//prompt loop, unfinished strings and any-base numbers continue on the next line
+	let mut pending = String::new();
+	loop {
+		let line = match &mut editor {
+			Some(ed) => {
+				match ed.readline(&if pending.is_empty() {prompt(dc)} else {". ".into()}) {
+					Ok(line) => Some(line),
+					Err(ReadlineError::Interrupted) => {	//discard line and pending input
+						pending.clear();
+						continue;
 					},
+					Err(ReadlineError::Eof) => None,
 					Err(error) => {
-						eprintln!("! Unable to read file \"{}\": {}", files[i], error);
+						eprintln!("! Unable to read standard input: {}", error);
+						break;
 					},
 				}
+			},
+			None => {
+				let mut input = String::new();
+				match stdin().read_line(&mut input) {
+					Ok(_) => {},
+					Err(error) => {
+						eprintln!("! Unable to read standard input: {}", error);
+						break;
+					}
+				}
+				if input.is_empty() {
+					None	//end of pipe input
+				}
+				else {
+					Some(input.trim_end_matches('\n').to_string())	//remove trailing LF
+				}
+			},
+		};
+		let eof = match line {
+			Some(line) => {
+				if !pending.is_empty() {pending.push('\n');}
+				pending.push_str(&line);
+				if is_unfinished(&pending) {
+					continue;
+				}
+				false
+			},
+			None if pending.is_empty() => {break;},
+			None => true,	//execute unfinished input to report the error
+		};
+		let input = std::mem::take(&mut pending);
+
+		if let Some(ed) = &mut editor {
+			if ed.add_history_entry(&input).unwrap_or(false) {
+				if let Some(file) = history_file() {
+					//saved right away since q exits directly
+					if let Err(error) = std::fs::create_dir_all(file.parent().unwrap()).and_then(|_| ed.append_history(&file).map_err(std::io::Error::other)) {
+						eprintln!("! Unable to save history: {}", error);
+					}
+				}
 			}
 		}
-	}
-}
-
-//checks if n arguments are sufficient for a command (defines adicity)
-//not used by niladics
-fn check_n(op: char, n: usize) -> bool {
-	if match op {
-		//triadic
-		'|' => n>=3,
 
-		//dyadic
-		'+'|'-'|'*'|'/'|'^'|'V'|'G'|'%'|'~'|'@'|':'|'='|'<'|'>'|'X' => n>=2,
-
-		//monadic unless specified
-		_ => n>=1,
-	}
-	{ true }
-	else {
-		eprintln!("! Insufficient arguments for command '{}'", op);
-		false
+		match input.trim() {
+			":undo" => {
+				match undo.pop() {
+					Some(snap) => {
+						redo.extend(current.replace(snap.clone()));
+						dc.restore(snap);
+						generation = dc.generation();
+					},
+					None => {
+						eprintln!("! Nothing to undo");
+					},
+				}
+				continue;
+			},
+			":redo" => {
+				match redo.pop() {
+					Some(snap) => {
+						undo.extend(current.replace(snap.clone()));
+						dc.restore(snap);
+						generation = dc.generation();
+					},
+					None => {
+						eprintln!("! Nothing to redo");
+					},
+				}
+				continue;
+			},
+			_ => {},
+		}
+		//errors don't end the session, even in strict mode
+		match std::panic::catch_unwind(AssertUnwindSafe(|| exec(dc, &input, None))) {
+			Ok(Ok(Some(code))) => {
+				quit(dc, code);
+			},
+			Ok(_) => {},
+			Err(_) => {	//already reported by the panic hook
+				EXECUTING.store(false, Ordering::Relaxed);
+			},
+		}
+		//lines that don't change anything can't be undone and keep what was undone redoable
+		if current.is_some() && dc.generation()!=generation {
+			let after = dc.snapshot();
+			if current.as_ref()!=Some(&after) {
+				if undo.len()==levels {
+					undo.remove(0);
+				}
+				undo.extend(current.replace(after));
+				redo.clear();
+			}
+			generation = dc.generation();
+		}
+		show_stack(dc);
+		if eof {
+			break;
+		}
 	}
 }
 
-//checks if a command can be used on provided argument types
-//a-c: types (.t) of the operands that would be used (in canonical order), use false if not required
-fn check_t(op: char, a: bool, b: bool, c: bool) -> bool {
-	if match op {
-		//'+' can also concatenate strings
-		'+' => (!a&&!b)||(a&&b),
-
-		//string manipulation, store into array
-		'-'|'*'|'/'|'~'|':' => (!a&&!b)||(a&&!b),
-
-		//read file by name, get env variable, execute os command
-		'&'|'$'|'\\' => a,
-
-		//convert both ways, constant lookup by string name or convert number to string, execute macros, get log or string length
-		'a'|'A'|'"'|'x'|'g' => !a||a,
-
-		//auto-macro
-		'X' => a&&!b,
-
-		//all other ops can only have numbers
-		_ => !a&&!b&&!c,
-	}
-	{ true }
-	else {
-		eprintln!("! Invalid argument type(s) for command '{}'", op);
-		false
-	}
-}
+const DEBUGHELP: &str = "Debugger commands:
+	s or empty line	execute the next command
+	c	continue until a breakpoint is reached
+	b r	break when the macro in register r is invoked (by x, <, = or >, also with the DRS), b alone lists breakpoints
+	d r	delete breakpoint on register r
+	f	print the main stack
+	r r	print the contents of register r
+	w	print the call stack (remaining commands of every macro, innermost first)
+	q	stop execution
+";
 
-//scientific notation to Some(Float), for brevity
-fn sci_to_flt(prec: u32, man: i128, exp: i128) -> Option<Float> {
-	Some(Float::with_val(prec, man)*Float::with_val(prec, exp).exp10())
+//tracer and/or debugger, debugger commands are read from stdin
+fn debug_hook(trace: bool, debug: bool, dc: &Interpreter) -> DebugHook {
+	let interrupt = dc.interrupt_flag();
+	let mut stepping = debug;
+	let mut breakpoints: Vec<usize> = Vec::new();
+	Box::new(move |dc: &Interpreter, cs: &CallStack| {
+		let cmd = cs.next_cmd().unwrap_or(' ');
+		let top = dc.stack().last().map(|obj| dc.format_obj(obj)).unwrap_or_else(|| "-".into());
+		if trace {
+			eprintln!("{:>3} {}  {}", cs.depth(), cmd, top);
+		}
+		if !debug {
+			return;
+		}
+		if let Some(ri) = breakpoints.iter().copied().find(|ri| {
+			dc.register(*ri).last().and_then(|ro| ro.o.as_str()).is_some_and(|mac| cs.starts_macro(mac))
+		}) {
+			eprintln!("Breakpoint: macro in register {} ({}) invoked", char::from_u32(ri as u32).unwrap_or('?'), ri);
+			stepping = true;
+		}
+		if !stepping {
+			return;
+		}
+		let here = cs.location().map(|loc| format!(" at {}", loc)).unwrap_or_default();
+		eprintln!("[depth {}{}] next: {}  top: {}", cs.depth(), here, cmd, top);
+		loop {
+			eprint!("debug> ");
+			let mut input = String::new();
+			if stdin().read_line(&mut input).unwrap_or(0)==0 {
+				stepping = false;	//no more debugger commands, just run
+				return;
+			}
+			let input = input.trim_end_matches(['\n', '\r']);
+			let mut chars = input.chars();
+			let dcmd = chars.next();
+			let reg = chars.find(|c| *c!=' ').map(|c| c as usize);
+			match (dcmd, reg) {
+				(None|Some('s'), _) => {return;},
+				(Some('c'), _) => {
+					stepping = false;
+					return;
+				},
+				(Some('b'), Some(ri)) => {
+					if !breakpoints.contains(&ri) {breakpoints.push(ri);}
+				},
+				(Some('b'), None) => {
+					for ri in &breakpoints {
+						eprintln!("{} ({})", char::from_u32(*ri as u32).unwrap_or('?'), ri);
+					}
+				},
+				(Some('d'), Some(ri)) => {
+					breakpoints.retain(|bp| *bp!=ri);
+				},
+				(Some('f'), _) => {
+					for obj in dc.stack().iter().rev() {
+						eprintln!("{}", dc.format_obj(obj));
+					}
+				},
+				(Some('r'), Some(ri)) => {
+					let reg = dc.register(ri);
+					if reg.is_empty() {
+						eprintln!("Register is empty");
+					}
+					for (li, ro) in reg.iter().enumerate().rev() {
+						eprintln!("layer {}: {} (array length {})", li, dc.format_obj(&ro.o), ro.a.len());
+					}
+				},
+				(Some('w'), _) => {
+					let count = cs.frames().count();
+					for (i, frame) in cs.frames().enumerate() {
+						eprintln!("{:>3} {}", count-i, frame);
+					}
+				},
+				(Some('q'), _) => {
+					interrupt.store(true, Ordering::Relaxed);
+					stepping = false;
+					return;
+				},
+				_ => {eprint!("{}", DEBUGHELP);},
+			}
+		}
+	})
 }
 
-//library of constants and unit conversion factors
-//unless specified, unit factors are based on the most prevalent international standard units for their respective quantities
-//ex: "in" (inch) returns 0.0254, thus executing 20[in]"* converts 20 inches to meters (0.508)
-fn constants(prec: u32, key: String) -> Option<Float> {
-	match key.as_str() {
-		/*----------------------------
-			MATHEMATICAL CONSTANTS
-		----------------------------*/
-		"e" => {Some(Float::with_val(prec, 1).exp())}
-		"pi" => {Some(Float::with_val(prec, Constant::Pi))}
-		"gamma" => {Some(Float::with_val(prec, Constant::Euler))}
-		"phi" => {Some((Float::with_val(prec, 5).sqrt()+1)/2)}
-		"deg"|"°" => {Some(Float::with_val(prec, Constant::Pi)/180)}
-		"gon"|"grad" => {Some(Float::with_val(prec, Constant::Pi)/200)}
-		/*------------------------
-			PHYSICAL CONSTANTS
-		------------------------*/
-		"c" => {Some(Float::with_val(prec, 299792458))}
-		"hbar" => {Some(sci_to_flt(prec, 662607015, -42).unwrap()/(2*Float::with_val(prec, Constant::Pi)))}
-		"G" => {sci_to_flt(prec, 6674, -3)}
-		"qe" => {sci_to_flt(prec, 1602176634, -28)}
-		"NA" => {sci_to_flt(prec, 602214076, 31)}
-		"kB" => {sci_to_flt(prec, 1380649, -29)}
-		"u" => {sci_to_flt(prec, 1660539066, -36)}
-		"lp" => {sci_to_flt(prec, 16162, -39)}
-		"tp" => {sci_to_flt(prec, 5391, -47)}
-		"mp" => {sci_to_flt(prec, 21764, -12)}
-		"Tp" => {sci_to_flt(prec, 14167, 28)}
-		/*------------------
-			LENGTH UNITS
-		------------------*/
-		"in" => {sci_to_flt(prec, 254, -4)}
-		"ft" => {Some(constants(prec, "in".to_string()).unwrap()*12)}
-		"yd" => {Some(constants(prec, "ft".to_string()).unwrap()*3)}
-		"m" => {Some(Float::with_val(prec, 1))}
-		"fur" => {Some(constants(prec, "ft".to_string()).unwrap()*660)}
-		"mi" => {Some(constants(prec, "ft".to_string()).unwrap()*5280)}
-		"nmi" => {Some(Float::with_val(prec, 1852))}
-		"AU" => {Some(Float::with_val(prec, 149597870700i64))}
-		"ly" => {Some(Float::with_val(prec, 9460730472580800i64))}
-		"pc" => {Some(Float::with_val(prec, 96939420213600000i64)/Float::with_val(prec, Constant::Pi))}
-		/*-------------------------------
-			   AREA & VOLUME UNITS
-			with no length equivalent
-		-------------------------------*/
-		"ac"|"acre" => {sci_to_flt(prec, 40468564224, -7)}
-		"l" => {Some(Float::with_val(prec, 10).pow(-3))}
-		"ifloz" => {sci_to_flt(prec, 284130625, -13)}
-		"ipt" => {Some(constants(prec, "ifloz".to_string()).unwrap()*20)}
-		"iqt" => {Some(constants(prec, "ifloz".to_string()).unwrap()*40)}
-		"igal" => {Some(constants(prec, "ifloz".to_string()).unwrap()*160)}
-		"ibu"|"ibsh" => {Some(constants(prec, "ifloz".to_string()).unwrap()*1280)}
-		"ufldr" => {sci_to_flt(prec, 36966911953125, -19)}
-		"tsp" => {Some(constants(prec, "ufldr".to_string()).unwrap()/3*4)}
-		"tbsp" => {Some(constants(prec, "ufldr".to_string()).unwrap()*4)}
-		"ufloz" => {Some(constants(prec, "ufldr".to_string()).unwrap()*8)}
-		"upt" => {Some(constants(prec, "ufloz".to_string()).unwrap()*16)}
-		"uqt" => {Some(constants(prec, "ufloz".to_string()).unwrap()*32)}
-		"ugal" => {Some(constants(prec, "ufloz".to_string()).unwrap()*128)}
-		"bbl" => {Some(constants(prec, "ugal".to_string()).unwrap()*42)}
-		"udpt" => {sci_to_flt(prec, 5506104713575, -16)}
-		"udqt" => {Some(constants(prec, "udpt".to_string()).unwrap()*2)}
-		"udgal" => {Some(constants(prec, "udpt".to_string()).unwrap()*8)}
-		"ubu"|"ubsh" => {Some(constants(prec, "udpt".to_string()).unwrap()*64)}
-		"dbbl" => {sci_to_flt(prec, 115627123584, -12)}
-		/*----------------
-			MASS UNITS
-		----------------*/
-		"ct" => {sci_to_flt(prec, 2, -4)}
-		"oz" => {sci_to_flt(prec, 28349523125, -12)}
-		"lb" => {Some(constants(prec, "oz".to_string()).unwrap()*16)}
-		"kg" => {Some(Float::with_val(prec, 1))}
-		"st" => {Some(constants(prec, "lb".to_string()).unwrap()*14)}
-		"t" => {Some(constants(prec, "lb".to_string()).unwrap()*2240)}
-		/*----------------
-			TIME UNITS
-		----------------*/
-		"s" => {Some(Float::with_val(prec, 1))}
-		"min" => {Some(Float::with_val(prec, 60))}
-		"h" => {Some(constants(prec, "min".to_string()).unwrap()*60)}
-		"d" => {Some(constants(prec, "h".to_string()).unwrap()*24)}
-		"w" => {Some(constants(prec, "d".to_string()).unwrap()*7)}
-		/*-----------------
-			OTHER UNITS
-		-----------------*/
-		"J" => {Some(Float::with_val(prec, 1))}
-		"cal" => {sci_to_flt(prec, 4184, -3)}
-		"Pa" => {Some(Float::with_val(prec, 1))}
-		"atm" => {Some(Float::with_val(prec, 101325))}
-		"psi" => {sci_to_flt(prec, 6894757293168, -9)}
-		/*------------------------------
-			SPECIAL VALUES/FUNCTIONS
-		------------------------------*/
-		"time" => {Some(Float::with_val(prec, SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or(Duration::ZERO).as_secs()))}
-		"timens" => {Some(Float::with_val(prec, SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or(Duration::ZERO).as_nanos()))}
-		"pid" => {Some(Float::with_val(prec, std::process::id()))}
-		"abort" => {std::process::abort();}
-		"crash" => {constants(prec, "crash".to_string())}	//stack overflow through recursion
-		"panic" => {std::panic::panic_any(
-			unsafe {if let Some(ptr) = MSTK.last() {
-				if ptr.t {&ptr.s} else {"Manual panic"}}
-			else {"Manual panic"}});}
-		"author" => {Some(Float::with_val(prec, 43615))}	//why not
-		_ => {
-			eprintln!("! Constant/conversion factor \"{}\" doesn't exist", key);
-			None
+//prompt string from register >, with placeholders replaced
+fn prompt(dc: &Interpreter) -> String {
+	let Some(fmt) = dc.register('>' as usize).last().and_then(|ro| ro.o.as_str()) else {
+		return "> ".into();
+	};
+	let (k, i, o) = dc.params();
+	let mut res = String::new();
+	let mut chars = fmt.chars();
+	while let Some(c) = chars.next() {
+		if c=='%' {
+			match chars.next() {
+				Some('z') => {res.push_str(&dc.stack().len().to_string());},
+				Some('i') => {res.push_str(&i.to_string());},
+				Some('o') => {res.push_str(&o.to_string());},
+				Some('k') => {res.push_str(&k.to_string());},
+				Some('w') => {res.push_str(&dc.wprec().to_string());},
+				Some('%') => {res.push('%');},
+				Some(other) => {res.push('%'); res.push(other);},
+				None => {res.push('%');},
+			}
+		}
+		else {
+			res.push(c);
 		}
 	}
+	res
 }
 
-//slightly more efficient string reverser, at least on my machine
-fn rev_str(mut instr: String) -> String {
-	let mut outstr = String::new();
-	while !instr.is_empty() {
-		outstr.push(instr.pop().unwrap());
+//prints as many stack objects as the number in register < says
+fn show_stack(dc: &Interpreter) {
+	let Some(count) = dc.register('<' as usize).last().and_then(|ro| ro.o.as_num())
+		.and_then(|n| n.to_integer()).and_then(|n| n.to_usize()) else {
+		return;
+	};
+	for obj in dc.stack().iter().rev().take(count) {
+		println!("{}", dc.format_obj(obj));
 	}
-	outstr
 }
 
-//custom number printing function
-//if output base is over 36, prints in custom "any-base" notation
-//otherwise, applies precision like dc and converts from exponential notation if not too small
-fn flt_to_str(mut num: Float, obase: Integer, oprec: Integer) -> String {
-	if num.is_zero() {
-		return String::from(if obase>36 {"(0)"} else {"0"});	//causes issues, always "0" regardless of parameters
-	}
-	if num.is_infinite() {
-		return String::from("Infinity");
-	}
-	if num.is_nan() {
-		return String::from("Not a number");
+//executes the macro on every line of stdin, returns false if the arguments are invalid
+fn stream_mode(dc: &mut Interpreter, mut args: Vec<String>) -> bool {
+	let numbers = args.first().is_some_and(|arg| arg=="-n");
+	if numbers {
+		args.remove(0);
 	}
-
-	if obase>36 {	//any-base printing (original base conversion algorithm out of necessity, limited precision possible)
-		let mut outstr = String::from(if num<0 {"(-"} else {"("});	//apply negative sign
-		num = num.abs();
-		let mut scale: usize = 0;	//amount to shift fractional separator in output
-		while !num.is_integer()&&(oprec<0||scale<oprec) {	//turn into integer scaled by power of obase, apply output precision if enabled
-			let temp = num.clone() * &obase;	//preview scale-up
-			if temp.is_infinite() {	//possible with high precision due to Float's exponent limitation
-				num /= &obase;	//prevent overflow in later "extra precision" part
-				break;	//disregard further precision
+	let [mac] = &args[..] else {
+		eprintln!("! Stream mode needs exactly one macro, use h for option syntax help");
+		return false;
+	};
+	for (ln, line) in stdin().lines().enumerate() {
+		let line = match line {
+			Ok(line) => line,
+			Err(error) => {
+				eprintln!("! Unable to read standard input: {}", error);
+				return false;
+			},
+		};
+		if numbers {
+			match dc.parse_number(&line) {
+				Ok(num) => {
+					dc.stack_mut().push(Obj::n(num));
+				},
+				Err(e) => {
+					dc.report_error(&e, Location { source: Source::Input, line: ln+1, column: 1 });	//skip line
+					if dc.is_strict() {
+						quit(dc, e.exit_code());
+					}
+					continue;
+				},
 			}
-			num = temp;	//if ok, commit to scale-up
-			scale +=1;
-		}
-		num *= &obase;	//get extra precision for last digit
-		let mut int = num.to_integer().unwrap();	//convert to Integer
-		int /= &obase;	//undo extra precision
-		let mut dig = Integer::from(1);	//current digit value
-		while dig<=int {
-			dig *= &obase;	//get highest required digit value
 		}
-		dig /= &obase;	//correct off-by-one error
-		loop {	//separate into digits
-			let (quot, rem) = int.clone().div_rem_euc(dig.clone());	//separate into amount of current digit and remainder
-			outstr.push_str(&quot.to_string());	//print amount of current digit
-			outstr.push(' ');
-			int = rem;	//switch to remainder
-			if dig==1 {break;}	//stop when all digits done
-			dig /= &obase;	//switch to next lower digit
+		else {
+			dc.stack_mut().push(Obj::s(line));
 		}
-		if scale>0 {
-			if let Some((idx, _)) = outstr.rmatch_indices(' ').nth(scale) {	//find location for fractional separator
-				unsafe { outstr.as_bytes_mut()[idx] = '.' as u8; }	//and insert it
-			}
-			else {
-				outstr.insert_str(if outstr.starts_with("(-") {2} else {1}, "0.");	//number has no integer part, add "0."
-			}
-		}
-		outstr.pop();
-		outstr.push(')');
-		outstr
+		run(dc, mac, None);
 	}
-	else {	//normal printing
-		let mut outstr = num.to_string_radix(
-			obase.to_i32().unwrap(),
-			if oprec<0 {
-				None
-			}
-			else {
-				(oprec + Integer::from(
-					num.to_integer_round(Round::Zero).unwrap().0	//integer part of num
-					.to_string_radix(obase.to_i32().unwrap())	//...to string
-					.trim_start_matches('-').len())).to_usize() 	//...length without negative sign, print exactly if too large
-			}
-		);
-		if obase <= 10 {	//unify exponent symbol without searching the whole string
-			let im = outstr.len()-1;	//max index
-			unsafe {
-				let bytes = outstr.as_bytes_mut();
-				for ir in 0..=im {	//right offset
-					if ir>10 {break;}	//exponents cannot have more digits, longest is @-323228496
-					if bytes[im-ir]=='e' as u8 {
-						bytes[im-ir] = '@' as u8;	//replace
-						break;
-					}
-				}
-			}
+	true
+}
+
+fn expression_mode(dc: &mut Interpreter, exprs: Vec<String>) {
+	for i in 0..exprs.len() {
+		if i==exprs.len()-1&&exprs[i]=="?" {
+			interactive_mode(dc);	//if last expression is "?", enter prompt loop
 		}
-		if outstr.starts_with('-') {
-			outstr = outstr.replacen('-', "_", 1);	//replace negative sign
+		else {
+			run(dc, &exprs[i], None);
 		}
-		if outstr[if outstr.len()>11 {outstr.len()-11} else {0}..].contains('@') {	//efficiently check if in exponential notation
-			let (mut mpart, epart) = outstr.rsplit_once('@').unwrap();
-			mpart = mpart.trim_end_matches('0').trim_end_matches('.');	//remove trailing zeros from mantissa
-			let eint = epart.parse::<i32>().unwrap();	//isolate exponential part
-			if eint<0 && eint>-10 {
-				outstr = "0.".to_string() + &"0".repeat(eint.abs() as usize -1) + &mpart.replacen('.', "", 1);	//convert from exponential notation if not too small
-				if num<0 {
-					let (ipart, fpart) = outstr.split_once('_').unwrap();
-					outstr = "_".to_string() + ipart + fpart;	//move negative sign to front
-				}
-			}
-			else {
-				outstr = mpart.to_string() + "@" + &epart.replacen('-', "_", 1);	//reassemble, replace negative sign in exponent
-			}
+	}
+}
+
+//returns false if a file couldn't be read
+fn file_mode(dc: &mut Interpreter, files: Vec<String>) -> bool {
+	if files.is_empty() {
+		eprintln!("! No file name provided");
+		return false;
+	}
+	let mut ok = true;
+	for i in 0..files.len() {
+		if i==files.len()-1&&files[i]=="?"{
+			interactive_mode(dc);	//if last filename is "?", enter prompt loop
 		}
-		else {	//if in normal notation
-			if let Some((ipart, fpart)) = outstr.split_once('.') {
-				outstr = ipart.to_string() + "." + fpart.trim_end_matches('0');	//trim trailing zeros
-			}
+		else if !run_file(dc, &files[i]) {
+			ok = false;
 		}
-		outstr.trim_end_matches('.').to_string()	//remove fractional separator
 	}
+	ok
 }
 
-//CORE EXECUTION ENGINE
-//unsafe for accessing static mut objects across different runs
-unsafe fn exec(input: String) {
-	let mut cmdstk: Vec<String> = Vec::new();	//stack of reversed command strings to execute, enables pseudorecursive macro calls
-	let mut inv = false;	//invert next comparison
-	if !input.is_empty() {	//loop expects contents, do nothing if none provided
-		cmdstk.push(rev_str(input));	//all command strings are reversed since pop() is O(1)
+//executes a script file ("-" for stdin), returns false if it couldn't be read
+fn run_file(dc: &mut Interpreter, file: &str) -> bool {
+	let script = if file=="-" {
+		std::io::read_to_string(stdin())
 	}
-	while !cmdstk.is_empty() {	//last().unwrap() is guaranteed to not panic within
-	
-		let mut cmd = cmdstk.last_mut().unwrap().pop().unwrap();	//isolate first character as command
-
-		//defines behavior of all commands
-		match cmd {
-			/*------------------
-				OBJECT INPUT
-			------------------*/
-			//standard number input, force with single quote to use letters
-			'0'..='9'|'.'|'_'|'\''|'@' => {
-				if ENVSTK.last().unwrap().1>36 {
-					eprintln!("! Any-base input must be used for input bases over 36");
-				}
-				else {
-					let mut numstr = String::new();	//gets filled with number to be parsed later
-					let mut frac = false;	//'.' has already occurred
-					let mut neg = false;	//'_' has already occurred
-					let mut alpha = false;	//letters are used
-					if cmd == '\'' {
-						alpha = true;
-						cmd = cmdstk.last_mut().unwrap().pop().unwrap_or('\0');
-					}
-					//keep adding to numstr until number is finished
-					'STDNUM_FINISHED: loop {
-						//numbers, periods and exponential notation
-						if cmd.is_ascii_digit()||cmd == '.'||cmd == '@' {
-							if cmd == '.' { if frac { break 'STDNUM_FINISHED; } else { frac = true; } } //break on encountering second '.'
-							if cmd == '@' { neg = false; }	//allow for second negative sign in exponent
-							numstr.push(cmd);						
-						}
-						//'_' needs to be replaced with '-'
-						else if cmd == '_' {
-							if neg { break 'STDNUM_FINISHED; } else { neg = true; } //break on encountering second '_'
-							numstr.push('-');
-						}
-						//parse letters if number is prefixed with quote
-						else if cmd.is_ascii_alphabetic() {
-							if alpha {
-								numstr.push(cmd);							
-							}
-							else {
-								break 'STDNUM_FINISHED;
-							}
-						}
-						else {
-							break 'STDNUM_FINISHED;
-						}
-						cmd = cmdstk.last_mut().unwrap().pop().unwrap_or('\0');
-					}
-					cmdstk.last_mut().unwrap().push(cmd);	//restore first char that isn't part of the number
-					if numstr.starts_with('@') { numstr.insert(0, '1') }	//add implied 1 before exponential marker
-					if numstr.starts_with('.')||numstr.starts_with("-.") { numstr = numstr.replace('.', "0."); }	//add implied zero before fractional separator
-					if numstr.ends_with('.')||numstr.ends_with('-')||numstr.is_empty() { numstr.push('0'); }	//add implied zero at end
-					match Float::parse_radix(numstr.clone(), ENVSTK.last().unwrap().1.to_i32().unwrap()) {		
-						Ok(res) => {
-							MSTK.push(Obj::n(Float::with_val(WPREC, res)));
-						},
-						Err(error) => {
-							eprintln!("! Unable to parse number \"{}\": {}", numstr, error);
-						},
-					}
-				}
-			},
-
-			//any-base number input
-			'(' => {
-				let mut num = Integer::from(0);	//resulting number
-				if cmdstk.last().unwrap().is_empty() {
-					MSTK.push(Obj::n(Float::with_val(WPREC, num)));	//default to 0 if on end of input
-				}
-				else {
-					let ibase = ENVSTK.last().unwrap().1.clone();
-					let mut dig = String::new();	//digit being parsed
-					let mut neg = false;	//number negative?
-					let mut frac = false;	//fractional separator has occurred
-					let mut scale = Integer::from(1);	//scale to divide by, for non-integers
-					let mut exp = false;	//exponential symbol has occurred
-					'CANCEL_ABNUM: loop {
-						cmd = if let Some(c) = cmdstk.last_mut().unwrap().pop() {c} else {')'};	//get next character, finish number if not possible
-						match cmd {
-							'0'..='9' => {
-								dig.push(cmd);	//add numerals to digit
-							},
-							'-'|'_' => {
-								if neg {
-									eprintln!("! Unable to parse any-base number: more than one negative sign");
-									if let Some(idx) = cmdstk.last().unwrap().rfind(')') {
-										cmdstk.last_mut().unwrap().truncate(idx);	//remove rest of erroneous number
-									}
-									else {
-										cmdstk.last_mut().unwrap().clear();
-									}
-									break;
-								}
-								neg = true;
-							},
-							'.' => {
-								if frac {
-									eprintln!("! Unable to parse any-base number: more than one fractional separator");
-									if let Some(idx) = cmdstk.last().unwrap().rfind(')') {
-										cmdstk.last_mut().unwrap().truncate(idx);	//remove rest of erroneous number
-									}
-									else {
-										cmdstk.last_mut().unwrap().clear();
-									}
-									break;
-								}
-								frac = true;
-								cmdstk.last_mut().unwrap().push(' ');	//end digit in next iteration
-							},
-							'@' => {
-								exp = true;
-								cmdstk.last_mut().unwrap().push(' ');	//end digit in next iteration, exponent handled by finalizer
-							},
-							' '|')' => {	//if digit or whole number is finished
-								let digint = if dig.clone().is_empty() {Integer::ZERO} else {Integer::parse(dig.clone()).unwrap().complete()};	//parse digit, default to 0
-								if digint >= ibase {
-									eprintln!("! Unable to parse any-base number: digit '{}' is too high for base {}", digint, ibase);
-									if cmd==')' {break;}
-									else {
-										if let Some(idx) = cmdstk.last().unwrap().rfind(')') {
-											cmdstk.last_mut().unwrap().truncate(idx);	//remove rest of erroneous number
-										}
-										else {
-											cmdstk.last_mut().unwrap().clear();
-										}
-										break;
-									}
-								}
-								num *= ibase.clone();	//add digit to number: multiply old contents by radix...
-								num += digint;	//... and add new digit
-								dig.clear();
-								if frac {
-									scale *= ibase.clone();	//if fractional part has started, make scale keep up
-								}
-								let escale =	//power applied to input base for exponential notation
-								if exp {	//if exponential part has begun
-									let mut epart = String::new();
-									let mut eneg = false;
-									while !cmdstk.last().unwrap().is_empty() {
-										cmd = cmdstk.last_mut().unwrap().pop().unwrap();
-										match cmd {
-											'0'..='9' => {
-												epart.push(cmd);
-											},
-											'-'|'_' => {
-												if eneg {
-													eprintln!("! Unable to parse any-base number: more than one negative sign in exponent");
-													if let Some(idx) = cmdstk.last().unwrap().rfind(')') {
-														cmdstk.last_mut().unwrap().truncate(idx);	//remove rest of erroneous number
-													}
-													else {
-														cmdstk.last_mut().unwrap().clear();
-													}
-													break 'CANCEL_ABNUM;
-												}
-												epart.insert(0, '-');
-												eneg = true;
-											},
-											')' => {
-												break;
-											},
-											_ => {
-												eprintln!("! Unable to parse any-base number: invalid character '{}' in exponent", cmd);
-												if let Some(idx) = cmdstk.last().unwrap().rfind(')') {
-													cmdstk.last_mut().unwrap().truncate(idx);	//remove rest of erroneous number
-												}
-												else {
-													cmdstk.last_mut().unwrap().clear();
-												}
-												break 'CANCEL_ABNUM;
-											},
-										}
-									}
-									Integer::parse(epart).unwrap().complete()
-								}
-								else {
-									Integer::from(0)
-								};
-								if cmd==')' {	//if number finished, push to stack
-									if scale>1 {
-										scale /= ibase.clone();	//correct off-by-one error
-									}
-									MSTK.push(Obj::n(Float::with_val(WPREC, num * if neg {-1} else {1}) / scale
-										* Float::with_val(WPREC, ibase).pow(escale)));
-									break;
-								}
-							},
-							_ => {
-								eprintln!("! Invalid character in any-base number: '{}'", cmd);
-								if let Some(idx) = cmdstk.last().unwrap().rfind(')') {
-									cmdstk.last_mut().unwrap().truncate(idx);	//remove rest of erroneous number
-								}
-								else {
-									cmdstk.last_mut().unwrap().clear();
-								}
-								break;
-							},
-						}
-					}
-				}
-			},
+	else {
+		std::fs::read_to_string(file)
+	};
+	match script {
+		Ok(script) => {
+			run(dc, &strip_comments(&script), Some(if file=="-" {"<stdin>"} else {file}));
+			true
+		},
+		Err(error) => {
+			eprintln!("! Unable to read file \"{}\": {}", file, error);
+			if dc.is_strict() {
+				quit(dc, 1);
+			}
+			false
+		},
+	}
+}
 
-			//string input
-			'[' => {
-				let mut res = String::new();	//result string
-				let mut nest: usize = 1;	//nesting level
-				cmd = cmdstk.last_mut().unwrap().pop().unwrap_or('\0');	//overwrite opening bracket, null if nothing left
-				loop {
-					res.push(cmd);
-					if cmd == '[' { nest+=1; }
-					if cmd == ']' { nest-=1; }
-					if nest==0 {	//string finished
-						res.pop();	//remove closing bracket
-						MSTK.push(Obj::s(res));
-						break;
-					}
-					if cmdstk.last().unwrap().is_empty() {	//only reached on improper string
-						eprintln!("! Unable to parse string \"[{}\": missing closing bracket", res);
-						break;
-					}
-					else {cmd = cmdstk.last_mut().unwrap().pop().unwrap();}
-				}
-			},
-			/*--------------
-				PRINTING
-			--------------*/
-			//print top with newline
-			'p' => {
-				if !MSTK.is_empty() {
-					if MSTK.last().unwrap().t {
-						println!("[{}]", MSTK.last().unwrap().s.clone());
-					}
-					else {
-						println!("{}", flt_to_str(MSTK.last().unwrap().n.clone(), ENVSTK.last().unwrap().2.clone(), ENVSTK.last().unwrap().0.clone()));
-					}
-				}
-			},
+//$XDG_CONFIG_HOME/dcim or ~/.config/dcim, None if there is no home directory
+fn config_dir() -> Option<std::path::PathBuf> {
+	let base = std::env::var_os("XDG_CONFIG_HOME").map(std::path::PathBuf::from).filter(|dir| dir.is_absolute())	//relative paths are invalid by the XDG spec
+		.or_else(|| std::env::var_os("HOME").filter(|home| !home.is_empty()).map(|home| std::path::Path::new(&home).join(".config")))?;
+	Some(base.join("dcim"))
+}
 
-			//print full stack top to bottom
-			'f' => {
-				if !MSTK.is_empty() {
-					for i in (0..MSTK.len()).rev() {
-						if MSTK[i].t {
-							println!("[{}]", MSTK[i].s.clone());
-						}
-						else {
-							println!("{}", flt_to_str(MSTK[i].n.clone(), ENVSTK.last().unwrap().2.clone(), ENVSTK.last().unwrap().0.clone()));
-						}
-					}
-				}
-			},
+//history of the line editor, not kept if None
+fn history_file() -> Option<std::path::PathBuf> {
+	config_dir().map(|dir| dir.join("history"))
+}
 
-			//pop and print without newline
-			'n' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if a.t {
-						print!("{}", a.s);
-						stdout().flush().unwrap();
-					}
-					else {
-						print!("{}", flt_to_str(a.n, ENVSTK.last().unwrap().2.clone(), ENVSTK.last().unwrap().0.clone()));
-						stdout().flush().unwrap();
-					}
+//completes constant names in strings of the interactive line editor
+struct DcHelper;
+
+impl Completer for DcHelper {
+	type Candidate = Pair;
+
+	fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+		let Some(start) = open_string(&line[..pos]) else {
+			return Ok((pos, Vec::new()));
+		};
+		//only strings that are (or will be) looked up with "
+		let mut nest: usize = 1;
+		for (i, c) in line[pos..].char_indices() {
+			if c=='[' {nest += 1;}
+			if c==']' {nest -= 1;}
+			if nest==0 {
+				if !line[pos+i+1..].starts_with('"') {
+					return Ok((pos, Vec::new()));
 				}
-			},
+				break;
+			}
+		}
+		//current word of conversion shorthand, without scale prefix
+		let word = line[start..pos].rsplit(' ').next().unwrap();
+		let name = word.trim_start_matches(|c: char| c.is_ascii_digit()||c=='-');
+		let cands = CONSTANT_NAMES.iter()
+			.filter(|cn| cn.starts_with(name))
+			.map(|cn| Pair { display: cn.to_string(), replacement: cn.to_string() })
+			.collect();
+		Ok((pos-name.len(), cands))
+	}
+}
 
-			//pop and print with newline
-			'P' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if a.t {
-						println!("{}", a.s);
-					}
-					else {
-						println!("{}", flt_to_str(a.n, ENVSTK.last().unwrap().2.clone(), ENVSTK.last().unwrap().0.clone()));
-					}
-				}
-			},
+impl Hinter for DcHelper {
+	type Hint = String;
+}
 
-			//print register
-			'F' => {
-				if cmdstk.last().unwrap().is_empty()&&!DRS_EN {
-					eprintln!("! No register number provided");
-				}
-				else {
-					let ri = if DRS_EN {
-						DRS_EN = false;
-						DRS
-					}
-					else {
-						cmdstk.last_mut().unwrap().pop().unwrap() as usize
-					};
-					if REGS.len()>ri {
-						if !REGS[ri].is_empty(){
-							for i in (0..REGS[ri].len()).rev() {
-								if REGS[ri][i].o.t {
-									println!("[{}]", REGS[ri][i].o.s.clone());
-								}
-								else {
-									println!("{}", flt_to_str(REGS[ri][i].o.n.clone(), ENVSTK.last().unwrap().2.clone(), ENVSTK.last().unwrap().0.clone()));
-								}
-								if !REGS[ri][i].a.is_empty() {
-									let maxwidth = REGS[ri][i].a.len().to_string().len();	//length of longest index number
-									for ai in 0..REGS[ri][i].a.len() {
-										if REGS[ri][i].a[ai].t {
-											println!("\t{:>maxwidth$}: [{}]", ai, REGS[ri][i].a[ai].s);
-										}
-										else {
-											println!("\t{:>maxwidth$}: {}", ai, flt_to_str(REGS[ri][i].a[ai].n.clone(), ENVSTK.last().unwrap().2.clone(), ENVSTK.last().unwrap().0.clone()));
-										}
-									}
-								}
-							}
-						}
-					}
-					else {
-						eprintln!("! Register {} is not available", ri);
-					}
-				}
-			},
-			/*----------------
-				ARITHMETIC
-			----------------*/
-			//add or concatenate strings
-			'+' => {
-				if check_n(cmd, MSTK.len()) {
-					let b = MSTK.pop().unwrap();
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, b.t, false) {
-						//concat strings
-						if a.t {
-							MSTK.push(Obj::s(a.s + &b.s));
-						}
-						//add numbers
-						else {
-							MSTK.push(Obj::n(Float::with_val(WPREC, a.n + b.n)));
-						}
-					}
-				}
-			},
+impl Highlighter for DcHelper {}
 
-			//subtract or remove chars from string
-			'-' => {
-				if check_n(cmd, MSTK.len()) {
-					let b = MSTK.pop().unwrap();
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, b.t, false) {
-						//remove b chars from string a
-						if a.t {
-							let mut newstr = a.s.chars().collect::<Vec<char>>();
-							let int = b.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;	//extract b, keep for checking if negative
-							if let Some(mut num) = &int.abs_ref().complete().to_usize() {
-								if num>newstr.len() { num = newstr.len(); }	//account for too large b
-								if int<0 { newstr.reverse(); }	//if negative, reverse to remove from front
-								newstr.truncate(newstr.len()-num);
-								if int<0 { newstr.reverse(); }	//undo reversal
-								MSTK.push(Obj::s(newstr.iter().collect::<String>()));
-							}
-							else {
-								eprintln!("! Cannot possibly remove {} characters from a string", int);
-							}
-						}
-						//subtract numbers
-						else {
-							MSTK.push(Obj::n(Float::with_val(WPREC, a.n - b.n)));
-						}
-					}
-				}
-			},
+impl Validator for DcHelper {}
 
-			//multiply or repeat/invert string
-			'*' => {
-				if check_n(cmd, MSTK.len()) {
-					let b = MSTK.pop().unwrap();
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, b.t, false) {
-						//repeat string a b times
-						if a.t {
-							let mut newstr = a.s;
-							let int = b.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;	//extract b, keep for checking if negative
-							if let Some(mut num) = &int.abs_ref().complete().to_usize() {
-								if num*newstr.len()>usize::MAX { num = usize::MAX/newstr.len(); }	//account for too large b
-								newstr = newstr.repeat(num);
-								if int<0 { newstr = rev_str(newstr); }	//if b is negative, invert string
-								MSTK.push(Obj::s(newstr));
-							}
-							else {
-								eprintln!("! Cannot possibly repeat a string {} times", int);
-							}
-						}
-						//multiply numbers
-						else {
-							MSTK.push(Obj::n(Float::with_val(WPREC, a.n * b.n)));
-						}
-					}
-				}
-			},
-			
-			//divide or shorten string to length
-			'/' => {
-				if check_n(cmd, MSTK.len()) {
-					let b = MSTK.pop().unwrap();
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, b.t, false) {
-						//shorten string a to length b
-						if a.t {
-							let mut newstr = a.s.chars().collect::<Vec<char>>();
-							let int = b.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;	//extract b, keep for checking if negative
-							if let Some(num) = &int.abs_ref().complete().to_usize() {
-								if int<0 { newstr.reverse(); }	//if negative, reverse to remove from front
-								newstr.truncate(*num);
-								if int<0 { newstr.reverse(); }	//undo reversal
-								MSTK.push(Obj::s(newstr.iter().collect::<String>()));
-							}
-							else {
-								eprintln!("! Cannot possibly shorten a string to {} characters", int);
-							}
-						}
-						//divide numbers
-						else {
-							if b.n==0 {
-								eprintln!("! Arithmetic error: Attempted division by zero");
-							}
-							else {
-								MSTK.push(Obj::n(Float::with_val(WPREC, a.n / b.n)));
-							}
-						}
-					}
-				}
-			},
+impl Helper for DcHelper {}
 
-			//modulo, integers only
-			'%' => {
-				if check_n(cmd, MSTK.len()) {
-					let b = MSTK.pop().unwrap();
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, b.t, false) {
-						let ia = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						let ib = b.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						if ib==0 {
-							eprintln!("! Arithmetic error: Attempted reduction mod 0");
-						}
-						else {
-							MSTK.push(Obj::n(Float::with_val(WPREC, ia % ib)));
-						}
-					}
-				}
-			},
-
-			//euclidean division or split string
-			'~' => {
-				if check_n(cmd, MSTK.len()) {
-					let b = MSTK.pop().unwrap();
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, b.t, false) {
-						if a.t {
-							let int = b.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-							if let Some(mut idx) = &int.to_usize() {
-								let cvec = a.s.chars().collect::<Vec<char>>();
-								if idx>cvec.len() { idx=cvec.len(); }	//if too large, split at max index to preserve signature
-								MSTK.push(Obj::s(cvec[0..idx].iter().collect::<String>()));
-								MSTK.push(Obj::s(cvec[idx..].iter().collect::<String>()));
-							}
-							else {
-								eprintln!("! Cannot possibly split a string at character {}", int);
-							}
-						}
-						else {
-							let ia = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-							let ib = b.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-							if ib==0 {
-								eprintln!("! Arithmetic error: Attempted reduction mod 0");
-							}
-							else {
-								let (quot, rem)=ia.div_rem_euc(ib);
-								MSTK.push(Obj::n(Float::with_val(WPREC, quot)));
-								MSTK.push(Obj::n(Float::with_val(WPREC, rem)));
-							}
-						}
-					}
-				}
-			},
-
-			//exponentiation
-			'^' => {
-				if check_n(cmd, MSTK.len()) {
-					let b = MSTK.pop().unwrap();
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, b.t, false) {
-						if a.n<0&&b.n.clone().abs()<1{
-							eprintln!("! Arithmetic error: Roots of negative numbers are not allowed");
-						}
-						else {
-							MSTK.push(Obj::n(Float::with_val(WPREC, a.n.pow(b.n))));
-						}
-					}
-				}
-			},
-
-			//modular exponentiation, integers only
-			'|' => {
-				if check_n(cmd, MSTK.len()) {
-					let c = MSTK.pop().unwrap();
-					let b = MSTK.pop().unwrap();
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, b.t, c.t) {
-						let ia = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						let ib = b.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						let ic = c.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						if ic==0 {
-							eprintln!("! Arithmetic error: Attempted reduction mod 0");
-						}
-						else {
-							if let Ok(res) = ia.clone().pow_mod(&ib, &ic) {
-								MSTK.push(Obj::n(Float::with_val(WPREC, res)));
-							}
-							else {
-								eprintln!("! Arithmetic error: {} doesn't have an inverse mod {}", ia, ic);
-							}
-						}
-					}
-				}
-			},
-
-			//square root
-			'v' => {
-				if check_n(cmd, MSTK.len()){
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						if a.n<0 {
-							eprintln!("! Arithmetic error: Roots of negative numbers are not allowed");
-						}
-						else {
-							MSTK.push(Obj::n(Float::with_val(WPREC, a.n.sqrt())));
-						}
-					}
-				}
-			},
-
-			//bth root
-			'V' => {
-				if check_n(cmd, MSTK.len()){
-					let b = MSTK.pop().unwrap();
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, b.t, false) {
-						if a.n<0&&b.n.clone().abs()>1{
-							eprintln!("! Arithmetic error: Roots of negative numbers are not allowed");
-						}
-						else {
-							MSTK.push(Obj::n(Float::with_val(WPREC, a.n.pow(b.n.recip()))));
-						}
-					}
-				}
-			},
-
-			//length of string or natural logarithm
-			'g' => {
-				if check_n(cmd, MSTK.len()){
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						if a.t {
-							MSTK.push(Obj::n(Float::with_val(WPREC, a.s.chars().count())));
-						}
-						else {
-							if a.n<=0 {
-								eprintln!("! Arithmetic error: Logarithms of zero and negative numbers are not allowed");
-							}
-							else {
-								MSTK.push(Obj::n(Float::with_val(WPREC, a.n.ln())));
-							}
-						}
-					}
-				}
-			},
-
-			//base b logarithm
-			'G' => {
-				if check_n(cmd, MSTK.len()){
-					let b = MSTK.pop().unwrap();
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, b.t, false) {
-						if a.n<=0 {
-							eprintln!("! Arithmetic error: Logarithms of zero and negative numbers are not allowed");
-						}
-						else if b.n==1||b.n<=0{
-							eprintln!("! Arithmetic error: Logarithm base must be positive and not equal to 1");
-						}
-						else {
-							MSTK.push(Obj::n(Float::with_val(WPREC, a.n.ln()/b.n.ln())));
-						}
-					}
-				}
-			},
-
-			//sine
-			'u' => {
-				if check_n(cmd, MSTK.len()){
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						MSTK.push(Obj::n(Float::with_val(WPREC, a.n.sin())));
-					}
-				}
-			},
-
-			//cosine
-			'y' => {
-				if check_n(cmd, MSTK.len()){
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						MSTK.push(Obj::n(Float::with_val(WPREC, a.n.cos())));
-					}
-				}
-			},
-
-			//tangent
-			't' => {
-				if check_n(cmd, MSTK.len()){
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						MSTK.push(Obj::n(Float::with_val(WPREC, a.n.tan())));
-					}
-				}
-			},
-
-			//arc-sine
-			'U' => {
-				if check_n(cmd, MSTK.len()){
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						if a.n.clone().abs()>1 {
-							eprintln!("! Arithmetic error: Arc-sine of value outside [-1,1]");
-						}
-						else {
-							MSTK.push(Obj::n(Float::with_val(WPREC, a.n.asin())));
-						}
-					}
-				}
-			},
-
-			//arc-cosine
-			'Y' => {
-				if check_n(cmd, MSTK.len()){
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						if a.n.clone().abs()>1 {
-							eprintln!("! Arithmetic error: Arc-cosine of value outside [-1,1]");
-						}
-						else {
-							MSTK.push(Obj::n(Float::with_val(WPREC, a.n.acos())));
-						}
-					}
-				}
-			},
-
-			//arc-tangent
-			'T' => {
-				if check_n(cmd, MSTK.len()){
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						MSTK.push(Obj::n(Float::with_val(WPREC, a.n.atan())));
-					}
-				}
-			},
-
-			//random integer [0;a)
-			'N' => {
-				if check_n(cmd, MSTK.len()){
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						let int = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						if int<=0 {
-							eprintln!("! Upper bound for random value must be above 0");
-						}
-						else {
-							MSTK.push(Obj::n(Float::with_val(WPREC, int.random_below(&mut RNG[0]))));
-						}
-					}
-				}
-			},
-
-			//constant/conversion factor lookup or convert number to string
-			'"' => {
-				if check_n(cmd, MSTK.len()) {
-					let mut a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						if a.t {	//constant lookup
-							match a.s.matches(' ').count() {
-								0 => {	//normal lookup
-									let mut scale = String::new();
-									while a.s.starts_with(|c: char| c.is_ascii_digit()||c=='-') {
-										scale.push(a.s.remove(0));	//extract scale prefix
-									}
-									if scale.is_empty() {scale.push('0');}
-
-									let mut power = String::new();
-									while a.s.ends_with(|c: char| c.is_ascii_digit()) {
-										power.insert(0, a.s.pop().unwrap());	//extract power suffix
-									}
-									if power.is_empty() {power.push('1');}
-
-									if let Some(res) = constants(WPREC, a.s) {
-										MSTK.push(Obj::n(Float::with_val(WPREC, (res*Float::with_val(WPREC, Integer::parse(scale).unwrap().complete()).exp10())
-												.pow(Integer::parse(power).unwrap().complete()))));
-									}
-								},
-								1 => {	//conversion shorthand, everything is like the 0 case but twice
-									let (from, to) = a.s.split_once(' ').unwrap();
-									let mut sfrom = String::from(from);	//convert from this
-									let mut sto = String::from(to);	//to this
-
-									let mut kfrom = String::new();
-									while sfrom.starts_with(|c: char| c.is_ascii_digit()||c=='-') {
-										kfrom.push(sfrom.remove(0));	//extract scale prefix
-									}
-									if kfrom.is_empty() {kfrom.push('0');}
-									let mut pfrom = String::new();
-									while sfrom.ends_with(|c: char| c.is_ascii_digit()) {
-										pfrom.insert(0, sfrom.pop().unwrap());	//extract power suffix
-									}
-									if pfrom.is_empty() {pfrom.push('1');}
-
-									let mut kto = String::new();
-									while sto.starts_with(|c: char| c.is_ascii_digit()||c=='-') {
-										kto.push(sto.remove(0));	//extract scale prefix
-									}
-									if kto.is_empty() {kto.push('0');}
-									let mut pto = String::new();
-									while sto.ends_with(|c: char| c.is_ascii_digit()) {
-										pto.insert(0, sto.pop().unwrap());	//extract power suffix
-									}
-									if pto.is_empty() {pto.push('1');}
-
-									if let Some(nfrom) = constants(WPREC, sfrom.to_string()) {
-										if let Some(nto) = constants(WPREC, sto.to_string()) {
-											MSTK.push(Obj::n(Float::with_val(WPREC,
-													(nfrom*Float::with_val(WPREC, Integer::parse(kfrom).unwrap().complete()).exp10())
-														.pow(Integer::parse(pfrom).unwrap().complete())/
-													(nto*Float::with_val(WPREC, Integer::parse(kto).unwrap().complete()).exp10())
-														.pow(Integer::parse(pto).unwrap().complete()))));
-										}
-									}
-								},
-								_ => {
-									eprintln!("! Too many spaces in constant lookup/unit conversion string \"{}\"", a.s);
-								},
-							}
-						}
-						else {	//"print" number to string
-							MSTK.push(Obj::s(flt_to_str(a.n, ENVSTK.last().unwrap().2.clone(), ENVSTK.last().unwrap().0.clone())));
-						}
-					}
-				}
-			},
-			/*------------------------
-				STACK MANIPULATION
-			------------------------*/
-			//clear stack
-			'c' => {
-				MSTK.clear();
-			},
-
-			//remove top a objects from stack
-			'C' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						let int = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						if let Some(mut num) = int.to_usize() {
-							if num>MSTK.len() { num = MSTK.len(); }	//limit clear count
-							MSTK.truncate(MSTK.len()-num);
-						}
-						else {
-							eprintln!("! Cannot possibly remove {} objects from the main stack", int);
-						}
-					}
-				}
-			},
-
-			//duplicate top of stack
-			'd' => {
-				if MSTK.is_empty() {
-					eprintln!("! Nothing to duplicate");
-				}
-				else {
-					MSTK.extend_from_within(MSTK.len()-1..);
-				}
-			},
-
-			//duplicate top a objects
-			'D' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						let int = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						if let Some(num) = int.to_usize() {
-							if num<=MSTK.len() {
-								MSTK.extend_from_within(MSTK.len()-num..);
-							}
-							else {
-								eprintln!("! Not enough objects to duplicate");
-							}
-						}
-						else {
-							eprintln!("! Cannot possibly duplicate {} objects", int);
-						}
-					}
-				}
-			},
-
-			//swap top 2 objects
-			'r' => {
-				if MSTK.len()>=2 {
-					MSTK.swap(MSTK.len()-2, MSTK.len()-1);
-				}
-				else {
-					eprintln!("! Not enough objects to rotate")
-				}
-			},
-
-			//rotate top a objects
-			'R' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						let mut int = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						if int==0 { int = Integer::from(1); }	//replace 0 with effective no-op
-						if let Some(num) = int.clone().abs().to_usize() {
-							if num<=MSTK.len() {
-								let sl = MSTK.as_mut_slice();
-								if int<0 {
-									sl[MSTK.len()-num..].rotate_left(1);	//if negative, rotate left/down
-								}
-								else {
-									sl[MSTK.len()-num..].rotate_right(1);	//right/up otherwise
-								}
-								MSTK = sl.to_vec();
-							}
-							else {
-								eprintln!("! Not enough objects to rotate");
-							}
-						}
-						else {
-							eprintln!("! Cannot possibly rotate {} objects", int.abs());
-						}
-					}
-				}
-			},
-
-			//push stack depth
-			'z' => {
-				MSTK.push(Obj::n(Float::with_val(WPREC, MSTK.len())));
-			},
-			/*----------------------------
-				ENVIRONMENT PARAMETERS
-			----------------------------*/
-			//set output precision
-			'k' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						let int = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						if int>=-1 {
-							ENVSTK.last_mut().unwrap().0 = int;
-						}
-						else {
-							eprintln!("! Output precision must be at least -1");
-						}
-					}
-				}
-			},
-
-			//set input base
-			'i' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						let int = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						if int>=2 {
-							ENVSTK.last_mut().unwrap().1 = int;
-						}
-						else {
-							eprintln!("! Input base must be at least 2");
-						}
-					}
-				}
-			},
-
-			//set output base
-			'o' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						let int = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						if int>=2 {
-							ENVSTK.last_mut().unwrap().2 = int;
-						}
-						else {
-							eprintln!("! Output base must be at least 2");
-						}
-					}
-				}
-			},
-
-			//set working precision
-			'w' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						let int = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						if int>=1 && int<=u32::MAX {
-							WPREC = int.to_u32().unwrap();
-						}
-						else {
-							eprintln!("! Working precision must be between {} and {} (inclusive)", 1, u32::MAX);
-						}
-					}
-				}
-			},
-
-			//push output precision
-			'K' => {
-				MSTK.push(Obj::n(Float::with_val(WPREC, ENVSTK.last().unwrap().0.clone())));
-			},
-
-			//push input base
-			'I' => {
-				MSTK.push(Obj::n(Float::with_val(WPREC, ENVSTK.last().unwrap().1.clone())));
-			},
-
-			//push output base
-			'O' => {
-				MSTK.push(Obj::n(Float::with_val(WPREC, ENVSTK.last().unwrap().2.clone())));
-			},
-
-			//push working precision
-			'W' => {
-				MSTK.push(Obj::n(Float::with_val(WPREC, WPREC)));
-			},
-
-			//create new k,i,o context
-			'{' => {
-				ENVSTK.push((kdef(), idef(), odef()));
-			},
-
-			//revert to previous context
-			'}' => {
-				ENVSTK.pop();
-				if ENVSTK.is_empty() {
-					ENVSTK.push((kdef(), idef(), odef()));	//ensure 1 entry always remains
-				}
-			},
-			/*--------------------------
-				REGISTERS AND MACROS
-			--------------------------*/
-			//save to top of register
-			's' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();					
-					if cmdstk.last().unwrap().is_empty()&&!DRS_EN {
-						eprintln!("! No register number provided");
-					}
-					else {
-						let ri = if DRS_EN {
-							DRS_EN = false;
-							DRS
-						}
-						else {
-							cmdstk.last_mut().unwrap().pop().unwrap() as usize
-						};
-						if REGS.len()>ri {
-							if REGS[ri].is_empty() {
-								REGS[ri].push(RegObj {
-									o: a,
-									a: Vec::new()
-								});
-							}
-							else {
-								REGS[ri].last_mut().unwrap().o = a;
-							}
-						}
-						else {
-							eprintln!("! Register {} is not available", ri);
-						}
-					}
-				}
-				else {
-					if !DRS_EN {
-						cmdstk.last_mut().unwrap().pop();	//remove register name
-					}
-					DRS_EN = false;	//invalidate DRS
-				}
-			},
-
-			//push to top of register
-			'S' => {
-				if check_n(cmd, MSTK.len()) {
-					let a=RegObj {
-						o: MSTK.pop().unwrap(),
-						a: Vec::new()
-					};
-					if cmdstk.last().unwrap().is_empty()&&!DRS_EN {
-						eprintln!("! No register number provided");
-					}
-					else {
-						let ri = if DRS_EN {
-							DRS_EN = false;
-							DRS
-						}
-						else {
-							cmdstk.last_mut().unwrap().pop().unwrap() as usize
-						};
-						if REGS.len()>ri {
-							REGS[ri].push(a);
-						}
-						else {
-							eprintln!("! Register {} is not available", ri);
-						}
-					}
-				}
-				else {
-					if !DRS_EN {
-						cmdstk.last_mut().unwrap().pop();	//remove register name
-					}
-					DRS_EN = false;	//invalidate DRS
-				}
-			},
-
-			//load from top of register
-			'l' => {
-				if cmdstk.last().unwrap().is_empty()&&!DRS_EN {
-					eprintln!("! No register number provided");
-				}
-				else {
-					let ri = if DRS_EN {
-						DRS_EN = false;
-						DRS
-					}
-					else {
-						cmdstk.last_mut().unwrap().pop().unwrap() as usize
-					};
-					if REGS.len()>ri {
-						if REGS[ri].is_empty() {
-							eprintln!("! Register {} is empty", ri);
-						}
-						else {
-							MSTK.push(REGS[ri].last().unwrap().o.clone());
-						}
-					}
-					else {
-						eprintln!("! Register {} is not available", ri);
-					}
-				}
-			},
-
-			//pop from top of register
-			'L' => {
-				if cmdstk.last().unwrap().is_empty()&&!DRS_EN {
-					eprintln!("! No register number provided");
-				}
-				else {
-					let ri = if DRS_EN {
-						DRS_EN = false;
-						DRS
-					}
-					else {
-						cmdstk.last_mut().unwrap().pop().unwrap() as usize
-					};
-					if REGS.len()>ri {
-						if REGS[ri].is_empty() {
-							eprintln!("! Register {} is empty", ri);
-						}
-						else {
-							MSTK.push(REGS[ri].pop().unwrap().o);
-						}
-					}
-					else {
-						eprintln!("! Register {} is not available", ri);
-					}
-				}
-			},
-
-			//save to top-of-register's array
-			':' => {
-				if check_n(cmd, MSTK.len()) {
-					let b = MSTK.pop().unwrap();
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, b.t, false) {
-						if cmdstk.last().unwrap().is_empty()&&!DRS_EN {
-							eprintln!("! No register number provided");
-						}
-						else {
-							let ri = if DRS_EN {
-								DRS_EN = false;
-								DRS
-							}
-							else {
-								cmdstk.last_mut().unwrap().pop().unwrap() as usize
-							};
-							if REGS.len()>ri {
-								if REGS[ri].is_empty() {
-									REGS[ri].push(RegObj {
-										o: Obj::n(Float::with_val(WPREC, 0)),	//create default register object if empty
-										a: Vec::new()
-									});
-								}
-								let int = b.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-								if let Some(rai) = int.to_usize() {
-									if rai>=REGS[ri].last().unwrap().a.len() {
-										REGS[ri].last_mut().unwrap().a.resize(rai+1, Obj::n(Float::with_val(WPREC, 0)));	//extend if required, initialize with default objects
-									}
-									REGS[ri].last_mut().unwrap().a[rai] = a;
-								}
-								else {
-									eprintln!("! Cannot possibly save to array index {}", int);
-								}
-							}
-							else {
-								eprintln!("! Register {} is not available", ri);
-							}
-						}
-					}
-					else {
-						if !DRS_EN {
-							cmdstk.last_mut().unwrap().pop();	//remove register name
-						}
-						DRS_EN = false;	//invalidate DRS
-					}
-				}
-				else {
-					if !DRS_EN {
-						cmdstk.last_mut().unwrap().pop();	//remove register name
-					}
-					DRS_EN = false;	//invalidate DRS
-				}
-			},
-
-			//load from top-of-register's array
-			';' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						if cmdstk.last().unwrap().is_empty()&&!DRS_EN {
-							eprintln!("! No register number provided");
-						}
-						else {
-							let ri = if DRS_EN {
-								DRS_EN = false;
-								DRS
-							}
-							else {
-								cmdstk.last_mut().unwrap().pop().unwrap() as usize
-							};
-							if REGS.len()>ri {
-								if REGS[ri].is_empty() {
-									REGS[ri].push(RegObj {
-										o: Obj::n(Float::with_val(WPREC, 0)),	//create default register object if empty
-										a: Vec::new()
-									});
-								}
-								let int = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-								if let Some(rai) = int.to_usize() {
-									if rai>=REGS[ri].last().unwrap().a.len() {
-										REGS[ri].last_mut().unwrap().a.resize(rai+1, Obj::n(Float::with_val(WPREC, 0)));	//extend if required, initialize with default objects
-									}
-									MSTK.push(REGS[ri].last().unwrap().a[rai].clone());
-								}
-								else {
-									eprintln!("! Cannot possibly load from array index {}", int);
-								}
-							}
-							else {
-								eprintln!("! Register {} is not available", ri);
-							}
-						}
-					}
-					else {
-						if !DRS_EN {
-							cmdstk.last_mut().unwrap().pop();	//remove register name
-						}
-						DRS_EN = false;	//invalidate DRS
-					}
-				}
-				else {
-					if !DRS_EN {
-						cmdstk.last_mut().unwrap().pop();	//remove register name
-					}
-					DRS_EN = false;	//invalidate DRS
-				}
-			},
-
-			//load top-of-reg into buffer
-			'j' => {
-				if cmdstk.last().unwrap().is_empty()&&!DRS_EN {
-					eprintln!("! No register number provided");
-				}
-				else {
-					let ri = if DRS_EN {
-						DRS_EN = false;
-						DRS
-					}
-					else {
-						cmdstk.last_mut().unwrap().pop().unwrap() as usize
-					};
-					if REGS.len()>ri {
-						if REGS[ri].is_empty() {
-							eprintln!("! Register {} is empty", ri);
-						}
-						else {
-							RO_BUF[0] = REGS[ri].last().unwrap().clone();
-						}
-					}
-					else {
-						eprintln!("! Register {} is not available", ri);
-					}
-				}
-			},
-
-			//pop top-of-reg into buffer
-			'J' => {
-				if cmdstk.last().unwrap().is_empty()&&!DRS_EN {
-					eprintln!("! No register number provided");
-				}
-				else {
-					let ri = if DRS_EN {
-						DRS_EN = false;
-						DRS
-					}
-					else {
-						cmdstk.last_mut().unwrap().pop().unwrap() as usize
-					};
-					if REGS.len()>ri {
-						if REGS[ri].is_empty() {
-							eprintln!("! Register {} is empty", ri);
-						}
-						else {
-							RO_BUF[0] = REGS[ri].pop().unwrap();
-						}
-					}
-					else {
-						eprintln!("! Register {} is not available", ri);
-					}
-				}
-			},
-
-			//save buffer to top-of-reg
-			'h' => {
-				if cmdstk.last().unwrap().is_empty()&&!DRS_EN {
-					eprintln!("! No register number provided");
-				}
-				else {
-					let ri = if DRS_EN {
-						DRS_EN = false;
-						DRS
-					}
-					else {
-						cmdstk.last_mut().unwrap().pop().unwrap() as usize
-					};
-					if REGS.len()>ri {
-						REGS[ri].pop();
-						REGS[ri].push(RO_BUF[0].clone());
-					}
-					else {
-						eprintln!("! Register {} is not available", ri);
-					}
-				}
-			},
-
-			//push buffer to register
-			'H' => {
-				if cmdstk.last().unwrap().is_empty()&&!DRS_EN {
-					eprintln!("! No register number provided");
-				}
-				else {
-					let ri = if DRS_EN {
-						DRS_EN = false;
-						DRS
-					}
-					else {
-						cmdstk.last_mut().unwrap().pop().unwrap() as usize
-					};
-					if REGS.len()>ri {
-						REGS[ri].push(RO_BUF[0].clone());
-					}
-					else {
-						eprintln!("! Register {} is not available", ri);
-					}
-				}
-			},
-
-			//push register depth
-			'Z' => {
-				if cmdstk.last().unwrap().is_empty()&&!DRS_EN {
-					eprintln!("! No register number provided");
-				}
-				else {
-					let ri = if DRS_EN {
-						DRS_EN = false;
-						DRS
-					}
-					else {
-						cmdstk.last_mut().unwrap().pop().unwrap() as usize
-					};
-					if REGS.len()>ri {
-						MSTK.push(Obj::n(Float::with_val(WPREC, REGS[ri].len())));
-					}
-					else {
-						eprintln!("! Register {} is not available", ri);
-					}
-				}
-			},
-
-			//specify manual register index
-			',' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						let int = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						if let Some(ri) = int.to_usize() {
-							if REGS.len()>ri {
-								DRS = ri;
-								DRS_EN = true;
-							}
-							else {
-								eprintln!("! Register {} is not available", ri);
-							}
-						}
-						else {
-							eprintln!("! Register {} cannot possibly exist", int);
-						}
-					}
-				}
-			},
-			/*------------
-				MACROS
-			------------*/
-
-			//convert least significant 32 bits to one-char string or first char of string to number
-			'a' => {
-				if check_n(cmd, MSTK.len()) {
-					let mut a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						if a.t {
-							if a.s.is_empty() {
-								eprintln!("! Cannot convert empty string to number");
-							}
-							else {
-								MSTK.push(Obj::n(Float::with_val(WPREC, a.s.remove(0) as u32)));
-							}
-						}
-						else {
-							if let Some(ia) = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0.to_u32() {
-								if let Some(res) = char::from_u32(ia) {
-									MSTK.push(Obj::s(res.to_string()));
-								}
-								else {
-									eprintln!("! Unable to convert number {} to character: not a valid Unicode value", ia);
-								}
-							}
-							else {
-								eprintln!("! Unable to convert number {} to character: valid range is 0 to {}", a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0, u32::MAX);
-							}
-						}
-					}
-				}
-			},
-
-			//convert number to UTF-8 string or back
-			'A' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						if a.t {
-							MSTK.push(Obj::n(Float::with_val(WPREC, Integer::from_digits(a.s.as_bytes(), Order::Msf))));
-						}
-						else {
-							if let Ok(res) = String::from_utf8(a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0.to_digits::<u8>(Order::Msf)) {
-								MSTK.push(Obj::s(res));
-							}
-							else {
-								eprintln!("! Unable to convert number {} to string: not valid UTF-8", a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0);
-							}
-						}
-					}
-				}
-			},
-
-			//execute string as macro
-			'x' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if a.t {
-						if cmdstk.last().unwrap().is_empty() {
-							cmdstk.pop();	//optimize tail call
-						}
-						cmdstk.push(rev_str(a.s));
-					}
-					else {
-						MSTK.push(a);
-					}
-				}
-			},
-
-			//invert next conditional
-			'!' => {
-				inv = !inv;
-			},
-
-			//conditionally execute macro
-			'<'|'='|'>' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();	//deliberately reverse order
-					let b = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, b.t, false) {
-						let mut mac = String::new();
-						if cmdstk.last().unwrap().is_empty()&&!DRS_EN {
-							eprintln!("! No register name provided");
-						}
-						else {
-							let ri = if DRS_EN {
-								DRS_EN = false;
-								DRS
-							}
-							else {
-								cmdstk.last_mut().unwrap().pop().unwrap() as usize
-							};
-							if REGS.len()>ri {
-								if REGS[ri].is_empty() {
-									eprintln!("! Register {} is empty", ri);
-								}
-								else {
-									mac = REGS[ri].last().unwrap().clone().o.s;	//get macro if possible
-								}
-							}
-							else {
-								eprintln!("! Register {} is not available", ri);
-							}
-						}
-						if !mac.is_empty() {
-							if inv != match cmd {	//like xor
-								'<' => { a.n < b.n },
-								'=' => { a.n == b.n },
-								'>' => { a.n > b.n },
-								_ => {false},
-							}
-							{
-								if cmdstk.last().unwrap().is_empty() {
-									cmdstk.pop();	//optimize tail call
-								}
-								cmdstk.push(rev_str(mac));
-							}
-						}
-					}
-					else {
-						if !DRS_EN {
-							cmdstk.last_mut().unwrap().pop();	//remove register name
-						}
-						DRS_EN = false;	//invalidate DRS
-					}
-				}
-				else {
-					if !DRS_EN {
-						cmdstk.last_mut().unwrap().pop();	//remove register name
-					}
-					DRS_EN = false;	//invalidate DRS
-				}
-				inv = false;	//always reset inversion
-			},
-
-			//auto-macro
-			'X' => {
-				if check_n(cmd, MSTK.len()) {
-					let b = MSTK.pop().unwrap();
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, b.t, false) {
-						let int = b.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						if let Some(reps) = int.to_usize() {
-							if cmdstk.last().unwrap().is_empty() {
-								cmdstk.pop();	//optimize tail call
-							}
-							cmdstk.resize(cmdstk.len()+reps, rev_str(a.s));
-						}
-						else {
-							eprintln!("! Invalid macro repeat count: {}", int);
-						}
-					}
-				}
-			},
-
-			//quit dcim
-			'q' => {
-				std::process::exit(if DRS_EN {DRS as i32} else {0});
-			},
-
-			//quit a macro calls
-			'Q' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						let int = a.n.to_integer_round(Round::Zero).unwrap_or(INT_ORD_DEF).0;
-						if let Some(mut num) = int.to_usize() {
-							if num>cmdstk.len() {num=cmdstk.len();}
-							cmdstk.truncate(cmdstk.len()-num);
-							if cmdstk.is_empty() {
-								cmdstk.push(String::new());	//guarantee at least one object
-							}
-						}
-						else {
-							eprintln!("! Cannot possibly quit {} levels", int);
-						}
-					}
-				}
-			},
-
-			//prompt and execute
-			'?' => {
-				let mut prompt_in = String::new();
-				stdin().read_line(&mut prompt_in).expect("Unable to read input");
-				prompt_in = prompt_in.trim_end_matches(char::is_whitespace).to_string();	//trim trailing LF
-				if cmdstk.last().unwrap().is_empty() {
-					cmdstk.pop();	//optimize tail call
-				}
-				cmdstk.push(rev_str(prompt_in));
-			},
-
-			//execute file as script
-			'&' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						match std::fs::read_to_string(a.s.clone()) {
-							Ok(script) => {
-								let mut script_nc = String::new();	//script with comments removed
-								for line in script.split('\n') {
-									script_nc.push_str(line.split_once('#').unwrap_or((line,"")).0);	//remove comment on every line
-									script_nc.push('\n');
-								}
-								cmdstk.push(rev_str(script_nc));
-							},
-							Err(error) => {
-								eprintln!("! Unable to read file \"{}\": {}", a.s, error);
-							},
-						}
-					}
-				}
-			},
-
-			//get environment variable
-			'$' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						match std::env::var(&a.s) {
-							Ok(val) => {
-								MSTK.push(Obj::s(val));
-							},
-							Err(err) => {
-								eprintln!("! Unable to get value of \"{}\": {}", a.s, err);
-							},
-						}
-					}
-				}
-			},
-
-			//execute os command(s)
-			'\\' => {
-				if check_n(cmd, MSTK.len()) {
-					let a = MSTK.pop().unwrap();
-					if check_t(cmd, a.t, false, false) {
-						for oscmd in a.s.split(';') {
-							if let Some((var, val)) = oscmd.split_once('=') {	//set variable
-								std::env::set_var(var, val);
-							}
-							else {	//normal command
-								let mut args: Vec<&str> = oscmd.trim().split(' ').collect();
-								match std::process::Command::new(args.remove(0)).args(args).spawn() {
-									Ok(mut child) => {
-										if let Ok(stat) = child.wait() {
-											if let Some(code) = stat.code() {
-												if code!=0 {eprintln!("! OS command \"{}\" exited with code {}", oscmd, code);}
-											}
-										}
-									},
-									Err(error) => {
-										eprintln!("! Unable to execute OS command \"{}\": {}", oscmd, error);
-									},
-								}
-							}
-						}
-					}
-				}
-			},
-
-			//stop on beginning of #comment
-			'#' => {
-				cmdstk.last_mut().unwrap().clear();
-			},
-
-			//notify on invalid command, keep going
-			_ => {
-				if !cmd.is_whitespace()&&cmd!='\0' { eprintln!("! Invalid command: {} (U+{:04X})", cmd, cmd as u32); }
-			},
-		}
-		while let Some(ptr) = cmdstk.last() {
-			if ptr.is_empty() {
-				cmdstk.pop();
-			}
-			else{break;}
+//executes $DCIM_INIT or init.dc in the config directory, the latter only if it exists
+fn init_file(dc: &mut Interpreter) {
+	if let Some(file) = std::env::var_os("DCIM_INIT").filter(|f| !f.is_empty()) {
+		run_file(dc, &file.to_string_lossy());
+	}
+	else if let Some(dir) = config_dir() {
+		let file = dir.join("init.dc");
+		if file.exists() {
+			run_file(dc, &file.to_string_lossy());
 		}
 	}
 }
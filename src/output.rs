@@ -0,0 +1,72 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::io::Write;
+use std::rc::Rc;
+
+/// Destination for everything printed by `p`, `f`, `n`, `P` and `F`.
+pub trait Output {
+	/// Writes a string as-is.
+	fn write_str(&mut self, s: &str);
+
+	/// Makes everything written so far visible, called after printing without a newline.
+	fn flush(&mut self) {}
+
+	/// Enables `write!` and `writeln!`.
+	fn write_fmt(&mut self, args: fmt::Arguments) {
+		self.write_str(&args.to_string());
+	}
+}
+
+/// Prints to the process's standard output, the default.
+pub struct Stdout;
+
+impl Output for Stdout {
+	fn write_str(&mut self, s: &str) {
+		let _ = std::io::stdout().write_all(s.as_bytes());
+	}
+	fn flush(&mut self) {
+		let _ = std::io::stdout().flush();
+	}
+}
+
+/// Prints to the process's standard error.
+pub struct Stderr;
+
+impl Output for Stderr {
+	fn write_str(&mut self, s: &str) {
+		let _ = std::io::stderr().write_all(s.as_bytes());
+	}
+}
+
+/// Collects output in memory. Clones share the same contents, so keep one to read what the interpreter printed.
+/// ```
+/// let buf = dcim::Buffer::new();
+/// let mut dc = dcim::Interpreter::new();
+/// dc.set_output(Box::new(buf.clone()));
+/// dc.exec("2 3+p");
+/// assert_eq!(buf.take(), "5\n");
+/// ```
+#[derive(Clone, Default)]
+pub struct Buffer(Rc<RefCell<String>>);
+
+impl Buffer {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Copy of everything printed so far.
+	pub fn contents(&self) -> String {
+		self.0.borrow().clone()
+	}
+
+	/// Returns everything printed so far and clears the buffer.
+	pub fn take(&self) -> String {
+		self.0.take()
+	}
+}
+
+impl Output for Buffer {
+	fn write_str(&mut self, s: &str) {
+		self.0.borrow_mut().push_str(s);
+	}
+}
@@ -0,0 +1,83 @@
+use std::path::{Path, PathBuf};
+
+/// Capabilities of dc:im code that reach outside of the interpreter.
+///
+/// The default allows everything, [`Sandbox::locked`] allows nothing. Disabled commands fail with [`DcError::Forbidden`](crate::DcError::Forbidden).
+#[derive(Clone, Debug)]
+pub struct Sandbox {
+	/// `\`: run OS commands and set environment variables.
+	pub os_commands: bool,
+	/// `&`, `M`, `b`, `B` and `E`: execute files, save, load and export the state.
+	pub files: FileAccess,
+	/// `$`: read environment variables.
+	pub env_vars: bool,
+	/// `"`: the constants `abort`, `crash` and `panic`, which kill the process.
+	pub process_exit: bool,
+}
+
+/// Which files may be accessed.
+#[derive(Clone, Debug)]
+pub enum FileAccess {
+	All,
+	Nothing,
+	/// Only files inside of these directories (after resolving symlinks and `..`).
+	Within(Vec<PathBuf>),
+}
+
+impl Default for Sandbox {
+	fn default() -> Self {
+		Self::unrestricted()
+	}
+}
+
+impl Sandbox {
+	/// Everything is allowed.
+	pub fn unrestricted() -> Self {
+		Sandbox {
+			os_commands: true,
+			files: FileAccess::All,
+			env_vars: true,
+			process_exit: true,
+		}
+	}
+
+	/// Nothing is allowed, for running untrusted code.
+	pub fn locked() -> Self {
+		Sandbox {
+			os_commands: false,
+			files: FileAccess::Nothing,
+			env_vars: false,
+			process_exit: false,
+		}
+	}
+
+	/// Returns the path to use if this file may be accessed. The file itself doesn't need to exist yet.
+	pub fn check_file<P: AsRef<Path>>(&self, name: P) -> Option<PathBuf> {
+		match &self.files {
+			FileAccess::All => Some(name.as_ref().to_path_buf()),
+			FileAccess::Nothing => None,
+			FileAccess::Within(dirs) => {
+				let path = resolve(name.as_ref())?;
+				if dirs.iter().filter_map(|dir| dir.canonicalize().ok()).any(|dir| path.starts_with(dir)) {
+					Some(path)
+				}
+				else {
+					None
+				}
+			},
+		}
+	}
+}
+
+//canonicalize, or canonicalize the parent if the file doesn't exist
+fn resolve(path: &Path) -> Option<PathBuf> {
+	if let Ok(res) = path.canonicalize() {
+		return Some(res);
+	}
+	let name = path.file_name()?;
+	let parent = match path.parent() {
+		Some(p) if !p.as_os_str().is_empty() => p.canonicalize().ok()?,
+		_ => std::env::current_dir().ok()?,
+	};
+	Some(parent.join(name))
+}
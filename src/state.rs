@@ -0,0 +1,279 @@
+use rug::{Integer, Float};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use crate::{Interpreter, DcError, Obj, RegObj};
+
+/*
+	State file format, one item per line:
+
+	dcim state 1
+	wprec <bits>
+	drs <register number>|none
+	env <k> <i> <o>			(one line per parameter context, bottom first)
+	stack <count>			(followed by <count> objects, bottom first)
+	buffer					(followed by a register object)
+	register <number> <depth>	(followed by <depth> register objects, bottom first)
+	end
+
+	register object:	object, then "array <length>" and <length> objects
+	number object:		n <precision> <value>, value is nan, inf, -inf, 0, -0 or <hex mantissa>p<binary exponent>
+	string object:		s <string with \\, \n and \r escaped>
+*/
+
+const HEADER: &str = "dcim state 1";
+
+/// In-memory copy of the main stack, registers, register buffer, parameter stack and working precision, see [`Interpreter::snapshot`].
+///
+/// Snapshots are equal if restoring either of them results in the same state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+	mstk: Vec<Obj>,
+	regs: Vec<(usize, Vec<RegObj>)>,	//only nonempty registers
+	ro_buf: RegObj,
+	envstk: Vec<(Integer, Integer, Integer)>,
+	wprec: u32,
+}
+
+impl Interpreter {
+	/// Serializes the complete state (main stack, registers, register buffer, parameter stack, working precision and DRS) to a string.
+	///
+	/// Numbers are stored exactly, including their individual precision. Settings like the error handler or sandbox are not part of the state.
+	pub fn save_state(&self) -> String {
+		let mut out = String::new();
+		writeln!(out, "{}", HEADER).unwrap();
+		writeln!(out, "wprec {}", self.wprec).unwrap();
+		match self.drs {
+			Some(drs) => writeln!(out, "drs {}", drs).unwrap(),
+			None => writeln!(out, "drs none").unwrap(),
+		}
+		for (k, i, o) in &self.envstk {
+			writeln!(out, "env {} {} {}", k, i, o).unwrap();
+		}
+		writeln!(out, "stack {}", self.mstk.len()).unwrap();
+		for obj in &self.mstk {
+			write_obj(&mut out, obj);
+		}
+		writeln!(out, "buffer").unwrap();
+		write_regobj(&mut out, &self.ro_buf);
+		for (ri, reg) in &self.regs {
+			if !reg.is_empty() {
+				writeln!(out, "register {} {}", ri, reg.len()).unwrap();
+				for ro in reg {
+					write_regobj(&mut out, ro);
+				}
+			}
+		}
+		writeln!(out, "end").unwrap();
+		out
+	}
+
+	/// Copies the state for restoring it later, like [`save_state`](Self::save_state) without DRS and serialization.
+	pub fn snapshot(&self) -> Snapshot {
+		Snapshot {
+			mstk: self.mstk.clone(),
+			regs: self.regs.iter().filter(|(_, reg)| !reg.is_empty()).map(|(ri, reg)| (*ri, reg.clone())).collect(),
+			ro_buf: self.ro_buf.clone(),
+			envstk: self.envstk.clone(),
+			wprec: self.wprec,
+		}
+	}
+
+	/// Replaces the state with a [`Snapshot`], registers that were empty at that point are cleared.
+	pub fn restore(&mut self, snap: Snapshot) {
+		self.generation += 1;
+		self.mstk = snap.mstk;
+		self.regs = snap.regs.into_iter().collect();
+		self.ro_buf = snap.ro_buf;
+		self.envstk = snap.envstk;
+		self.wprec = snap.wprec;
+	}
+
+	/// Replaces the current state with one created by [`save_state`](Self::save_state).
+	///
+	/// If the state is invalid, nothing is changed.
+	pub fn load_state(&mut self, state: &str) -> Result<(), DcError> {
+		let mut rd = Reader { lines: state.lines(), ln: 0 };
+		match rd.state() {
+			Ok((wprec, drs, envstk, mstk, ro_buf, regs)) => {
+				self.generation += 1;
+				self.wprec = wprec;
+				self.drs = drs;
+				self.envstk = envstk;
+				self.mstk = mstk;
+				self.ro_buf = ro_buf;
+				self.regs = regs;
+				Ok(())
+			},
+			Err(reason) => {
+				Err(DcError::Parse { reason: format!("Invalid state in line {}: {}", rd.ln, reason) })
+			},
+		}
+	}
+}
+
+type State = (u32, Option<usize>, Vec<(Integer, Integer, Integer)>, Vec<Obj>, RegObj, BTreeMap<usize, Vec<RegObj>>);
+
+//reads state files line by line, keeps the line number for error messages
+struct Reader<'a> {
+	lines: std::str::Lines<'a>,
+	ln: usize,
+}
+
+impl<'a> Reader<'a> {
+	fn next(&mut self, what: &str) -> Result<&'a str, String> {
+		self.ln += 1;
+		self.lines.next().ok_or_else(|| format!("unexpected end, expected {}", what))
+	}
+
+	//next line with this prefix, returns the rest
+	fn item(&mut self, prefix: &str, what: &str) -> Result<&'a str, String> {
+		self.next(what)?.strip_prefix(prefix).ok_or_else(|| format!("expected {}", what))
+	}
+
+	fn state(&mut self) -> Result<State, String> {
+		if self.next("header")?!=HEADER {
+			return Err("not a dcim state file or unsupported version".into());
+		}
+		let wprec = self.item("wprec ", "working precision")?.parse::<u32>().ok()
+			.filter(|p| *p>=1)
+			.ok_or("invalid working precision")?;
+		let drs = match self.item("drs ", "DRS")? {
+			"none" => None,
+			num => Some(num.parse::<usize>().map_err(|_| "invalid DRS")?),
+		};
+
+		let mut envstk = Vec::new();
+		let mut line = self.next("parameters")?;
+		while let Some(params) = line.strip_prefix("env ") {
+			let params: Vec<Integer> = params.split(' ').filter_map(|p| p.parse().ok()).collect();
+			match &params[..] {
+				[k, i, o] if *k>=-1 && *i>=2 && *o>=2 => {envstk.push((k.clone(), i.clone(), o.clone()));},
+				_ => {return Err("invalid parameters".into());},
+			}
+			line = self.next("main stack")?;
+		}
+		if envstk.is_empty() {
+			return Err("expected parameters".into());
+		}
+
+		let count = line.strip_prefix("stack ").and_then(|c| c.parse::<usize>().ok())
+			.ok_or("expected main stack")?;
+		let mut mstk = Vec::new();
+		for _ in 0..count {
+			mstk.push(self.obj()?);
+		}
+
+		if self.next("register buffer")?!="buffer" {
+			return Err("expected register buffer".into());
+		}
+		let ro_buf = self.regobj()?;
+
+		let mut regs: BTreeMap<usize, Vec<RegObj>> = BTreeMap::new();
+		loop {
+			let line = self.next("register or end")?;
+			if line=="end" {break;}
+			let (ri, depth) = line.strip_prefix("register ").and_then(|r| r.split_once(' '))
+				.and_then(|(ri, depth)| Some((ri.parse::<usize>().ok()?, depth.parse::<usize>().ok()?)))
+				.ok_or("expected register or end")?;
+			for _ in 0..depth {
+				let ro = self.regobj()?;
+				regs.entry(ri).or_default().push(ro);
+			}
+		}
+		Ok((wprec, drs, envstk, mstk, ro_buf, regs))
+	}
+
+	fn regobj(&mut self) -> Result<RegObj, String> {
+		let o = self.obj()?;
+		let len = self.item("array ", "array")?.parse::<usize>().map_err(|_| "invalid array length")?;
+		let mut a = Vec::new();
+		for _ in 0..len {
+			a.push(self.obj()?);
+		}
+		Ok(RegObj { o, a })
+	}
+
+	fn obj(&mut self) -> Result<Obj, String> {
+		read_obj(self.next("object")?)
+	}
+}
+
+fn write_obj(out: &mut String, obj: &Obj) {
+	match obj {
+		Obj::Str(s) => {
+			let esc = s.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r");
+			writeln!(out, "s {}", esc).unwrap();
+		},
+		Obj::Num(n) => {
+			let val = if n.is_nan() {
+				"nan".to_string()
+			}
+			else if n.is_infinite() {
+				if n.is_sign_negative() {"-inf"} else {"inf"}.to_string()
+			}
+			else if n.is_zero() {
+				if n.is_sign_negative() {"-0"} else {"0"}.to_string()
+			}
+			else {
+				let (man, exp) = n.to_integer_exp().unwrap();	//finite and nonzero
+				format!("{:x}p{}", man, exp)
+			};
+			writeln!(out, "n {} {}", n.prec(), val).unwrap();
+		},
+	}
+}
+
+fn write_regobj(out: &mut String, ro: &RegObj) {
+	write_obj(out, &ro.o);
+	writeln!(out, "array {}", ro.a.len()).unwrap();
+	for obj in &ro.a {
+		write_obj(out, obj);
+	}
+}
+
+fn read_obj(line: &str) -> Result<Obj, String> {
+	if let Some(esc) = line.strip_prefix("s ") {
+		let mut s = String::with_capacity(esc.len());
+		let mut chars = esc.chars();
+		while let Some(c) = chars.next() {
+			if c=='\\' {
+				match chars.next() {
+					Some('\\') => s.push('\\'),
+					Some('n') => s.push('\n'),
+					Some('r') => s.push('\r'),
+					_ => {return Err("invalid escape sequence in string".into());},
+				}
+			}
+			else {
+				s.push(c);
+			}
+		}
+		Ok(Obj::s(s))
+	}
+	else if let Some(num) = line.strip_prefix("n ") {
+		let (prec, val) = num.split_once(' ').ok_or("invalid number")?;
+		let prec = prec.parse::<u32>().ok()
+			.filter(|p| *p>=rug::float::prec_min() && *p<=rug::float::prec_max())
+			.ok_or("invalid number precision")?;
+		let n = match val {
+			"nan" => Float::with_val(prec, rug::float::Special::Nan),
+			"inf" => Float::with_val(prec, rug::float::Special::Infinity),
+			"-inf" => Float::with_val(prec, rug::float::Special::NegInfinity),
+			"0" => Float::with_val(prec, rug::float::Special::Zero),
+			"-0" => Float::with_val(prec, rug::float::Special::NegZero),
+			_ => {
+				let (man, exp) = val.split_once('p').ok_or("invalid number")?;
+				let man = Integer::from_str_radix(man, 16).map_err(|_| "invalid number mantissa")?;
+				let exp = exp.parse::<i32>().map_err(|_| "invalid number exponent")?;
+				if man.significant_bits()>prec {
+					return Err("number mantissa is longer than its precision".into());
+				}
+				Float::with_val(prec, man) << exp
+			},
+		};
+		Ok(Obj::n(n))
+	}
+	else {
+		Err("expected object".into())
+	}
+}
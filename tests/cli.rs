@@ -0,0 +1,27 @@
+//exit codes of the dcim binary
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+//exit code of running dcim with these arguments and stdin
+fn status(args: &[&str], stdin: &str) -> i32 {
+	let mut child = Command::new(env!("CARGO_BIN_EXE_dcim"))
+		.args(["--no-init"]).args(args)
+		.stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null())
+		.spawn().unwrap();
+	child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+	child.wait().unwrap().code().unwrap()
+}
+
+#[test]
+fn quit_after_error() {
+	assert_eq!(status(&["-e", "1+", "q"], ""), 1);
+	assert_eq!(status(&["-e", "1+ 3,q"], ""), 3);	//DRS is used as it is
+	assert_eq!(status(&["-e", "1 q"], ""), 0);
+}
+
+#[test]
+fn stream_invalid_number() {
+	assert_eq!(status(&["-s", "-n", "p"], "x\n3\n"), 1);
+	assert_eq!(status(&["-s", "-n", "p"], "2\n3\n"), 0);
+}
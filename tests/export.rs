@@ -0,0 +1,36 @@
+//exported scripts recreate the exported objects exactly
+
+use dcim::{Interpreter, Obj};
+
+//register a after storing s in it, exporting that with the parameters set by setup and running the script in a new interpreter with the same parameters
+fn round_trip(setup: &str, s: &str) -> Vec<Obj> {
+	let mut dc = Interpreter::new();
+	dc.stack_mut().push(Obj::s(s.into()));
+	dc.exec("sa").unwrap();
+	dc.exec(setup).unwrap();
+	let script = dc.export_script(Some(&['a' as usize]));
+	let mut new = Interpreter::new();
+	new.exec(setup).unwrap();
+	new.exec(&script).unwrap();
+	assert!(new.stack().is_empty(), "script leaves objects on the stack:\n{}", script);
+	new.register('a' as usize).iter().map(|ro| ro.o.clone()).collect()
+}
+
+#[test]
+fn balanced_brackets() {
+	assert_eq!(round_trip("", "a[b]c"), [Obj::s("a[b]c".into())]);
+}
+
+#[test]
+fn unbalanced_brackets() {
+	for s in ["a]b[", "]]", "[[x", "]", "x[", "[]]["] {
+		assert_eq!(round_trip("", s), [Obj::s(s.into())], "{}", s);
+	}
+}
+
+#[test]
+fn unbalanced_brackets_other_base() {
+	//character codes are written in the input base
+	assert_eq!(round_trip("16i", "a]b["), [Obj::s("a]b[".into())]);
+	assert_eq!(round_trip("100i", "]x["), [Obj::s("]x[".into())]);
+}
@@ -0,0 +1,35 @@
+//the string length limit also applies to numbers that are converted to strings
+
+use dcim::{Interpreter, Buffer, DcError, LimitKind, Limits};
+
+//output and result of running code in a new interpreter with a string length limit
+fn run(max: usize, code: &str) -> (String, Result<Option<i32>, DcError>) {
+	let buf = Buffer::new();
+	let mut dc = Interpreter::new();
+	dc.set_output(Box::new(buf.clone()));
+	dc.set_error_handler(Box::new(|_, _| {}));
+	dc.set_limits(Limits { string_length: Some(max), ..Limits::default() });
+	let res = dc.exec(code);
+	(buf.take(), res)
+}
+
+fn is_string_limit(res: &Result<Option<i32>, DcError>) -> bool {
+	matches!(res, Err(DcError::LimitExceeded { kind: LimitKind::StringLength(_), .. }))
+}
+
+#[test]
+fn output_precision() {
+	assert!(is_string_limit(&run(100, "1@10k").1));
+	let (out, res) = run(100, "50k 1 4/ p");
+	assert_eq!(out, "0.25\n");
+	assert!(res.is_ok());
+}
+
+#[test]
+fn long_numbers() {
+	for cmd in ["p", "n", "P", "f", "\"", "saFa"] {
+		let (out, res) = run(100, &format!("1 3/ 100o {}", cmd));
+		assert!(out.is_empty() && is_string_limit(&res), "{}", cmd);
+	}
+	assert!(run(100, "1 3/ p").0.starts_with("0.3333"));	//default precision fits
+}
@@ -0,0 +1,46 @@
+//allocations that can't possibly succeed are reported as errors instead of aborting the process
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use dcim::{Interpreter, Buffer, DcError};
+
+//output and errors of running code in a new interpreter
+fn run(code: &str) -> (String, Vec<DcError>) {
+	let buf = Buffer::new();
+	let errors = Rc::new(RefCell::new(Vec::new()));
+	let mut dc = Interpreter::new();
+	dc.set_output(Box::new(buf.clone()));
+	let errs = Rc::clone(&errors);
+	dc.set_error_handler(Box::new(move |e, _| errs.borrow_mut().push(e.clone())));
+	dc.exec(code).unwrap();
+	let errors = errors.borrow().clone();
+	(buf.take(), errors)
+}
+
+#[test]
+fn save_to_huge_index() {
+	let (out, errors) = run("1 2 3 0 2 60^:a f");
+	assert_eq!(out, "3\n2\n1\n");
+	assert!(matches!(&errors[..], [DcError::InvalidArgument { cmd: ':', .. }]));
+}
+
+#[test]
+fn load_from_huge_index() {
+	let (out, errors) = run("1 2 60^;a f");
+	assert_eq!(out, "1\n");
+	assert!(matches!(&errors[..], [DcError::InvalidArgument { cmd: ';', .. }]));
+}
+
+#[test]
+fn array_still_usable() {
+	let (out, errors) = run("5 2 60^:a 7 3:a 3;ap 2;ap");
+	assert_eq!(out, "7\n0\n");
+	assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn repeat_string_huge_times() {
+	let (out, errors) = run("[a] 2 62^* zp");
+	assert_eq!(out, "0\n");
+	assert!(matches!(&errors[..], [DcError::InvalidArgument { cmd: '*', .. }]));
+}
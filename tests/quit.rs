@@ -0,0 +1,62 @@
+//'Q' exits macro invocations regardless of tail call optimization and 'X' repetitions
+
+use dcim::{Interpreter, Buffer};
+
+//output of running code in a new interpreter
+fn run(code: &str) -> String {
+	let buf = Buffer::new();
+	let mut dc = Interpreter::new();
+	dc.set_output(Box::new(buf.clone()));
+	dc.exec(code).unwrap();
+	buf.take()
+}
+
+#[test]
+fn quit_one_level() {
+	assert_eq!(run("[1p 1Q 2p]x 3p"), "1\n3\n");
+	assert_eq!(run("[[1p 1Q 2p]x 3p]x 4p"), "1\n3\n4\n");
+}
+
+#[test]
+fn quit_two_levels() {
+	assert_eq!(run("[[1p 2Q 2p]x 3p]x 4p"), "1\n4\n");
+}
+
+#[test]
+fn quit_from_tail_call() {
+	//inner macro replaces the finished outer one, which must still count as a level
+	assert_eq!(run("[[1p 2Q 2p]x]x 3p"), "1\n3\n");
+	assert_eq!(run("[[1p 2Q]sa 1 1=a]x 3p"), "1\n3\n");
+	assert_eq!(run("[[[1p 3Q]x]x]x 2p"), "1\n2\n");
+}
+
+#[test]
+fn quit_from_tail_recursion() {
+	//la calls itself in tail position, leaving the 4 previous invocations finished
+	assert_eq!(run("[3Q]sq [1+ d5=q lax]sa [0lax 7p]x 8p"), "7\n8\n");
+	assert_eq!(run("[7Q]sq [1+ d5=q lax]sa [0lax 7p]x 8p"), "8\n");
+}
+
+#[test]
+fn quit_repetition() {
+	//only the current repetition, the others go on
+	assert_eq!(run("[1p 1Q 2p]3X 9p"), "1\n1\n1\n9\n");
+}
+
+#[test]
+fn quit_repeating_macro() {
+	//exiting the invoker of 'X' stops all repetitions
+	assert_eq!(run("[[1p 2Q 2p]3X 8p]x 9p"), "1\n9\n");
+	assert_eq!(run("[[1p 2Q 2p]3X]x 9p"), "1\n9\n");
+}
+
+#[test]
+fn quit_everything() {
+	let buf = Buffer::new();
+	let mut dc = Interpreter::new();
+	dc.set_output(Box::new(buf.clone()));
+	dc.exec("[1p 5Q]x 2p").unwrap();
+	assert_eq!(buf.take(), "1\n");
+	dc.exec("3p").unwrap();	//next input isn't affected
+	assert_eq!(buf.take(), "3\n");
+}